@@ -0,0 +1,146 @@
+//! Checks single IDs, and streams of them, against the repeated-block rule.
+
+use std::io::{self, BufRead};
+
+use crate::int::{digit_len, multiplier};
+
+/// How an invalid ID is built: `block` written `repeats` times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repetition {
+    /// The shortest block that builds the ID, e.g. `12` for `121212`.
+    pub block: u64,
+    pub repeats: u32,
+}
+
+/// Whether `id` is a block of digits repeated at least twice, and if so its
+/// shortest block. Works on the number alone: an ID with `len` digits repeats
+/// a `period`-digit block exactly when it is that block times
+/// `1 + 10^period + ... + 10^(len - period)`.
+pub fn is_invalid_id(id: u64) -> Option<Repetition> {
+    let len = id.checked_ilog10()? as usize + 1;
+    (1..len)
+        .filter(|&period| len.is_multiple_of(period))
+        .find_map(|period| {
+            let multiplier = multiplier(len, period)?;
+            let block = id / multiplier;
+            (id.is_multiple_of(multiplier) && digit_len(block) == period).then_some(Repetition {
+                block,
+                repeats: (len / period) as u32,
+            })
+        })
+}
+
+/// What a pass over a stream of IDs found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// IDs read, not counting blank or malformed lines.
+    pub checked: u64,
+    pub invalid: u64,
+    pub invalid_sum: u128,
+    /// 1-based numbers of the lines that are not a `u64`.
+    pub malformed: Vec<usize>,
+}
+
+/// Checks one ID per line, calling `on_invalid(line, id, repetition)` for each
+/// invalid one. Blank lines are skipped and unreadable ones are listed in the
+/// summary; only I/O errors stop the pass.
+pub fn validate<R: BufRead>(
+    mut reader: R,
+    mut on_invalid: impl FnMut(usize, u64, Repetition),
+) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+    let mut line = String::new();
+    let mut number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(summary);
+        }
+        number += 1;
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let Ok(id) = text.parse::<u64>() else {
+            summary.malformed.push(number);
+            continue;
+        };
+        summary.checked += 1;
+        if let Some(repetition) = is_invalid_id(id) {
+            summary.invalid += 1;
+            summary.invalid_sum += id as u128;
+            on_invalid(number, id, repetition);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::shortest_block;
+
+    fn by_string(id: u64) -> Option<Repetition> {
+        let s = id.to_string();
+        shortest_block(&s).map(|p| Repetition {
+            block: s[..p].parse().unwrap(),
+            repeats: (s.len() / p) as u32,
+        })
+    }
+
+    #[test]
+    fn test_is_invalid_id() {
+        assert_eq!(
+            is_invalid_id(121212),
+            Some(Repetition {
+                block: 12,
+                repeats: 3
+            })
+        );
+        assert_eq!(
+            is_invalid_id(222222),
+            Some(Repetition {
+                block: 2,
+                repeats: 6
+            })
+        );
+        assert_eq!(is_invalid_id(1188511885).unwrap().block, 11885);
+        assert_eq!(is_invalid_id(0), None);
+        assert_eq!(is_invalid_id(7), None);
+        assert_eq!(is_invalid_id(1001), None);
+        // Blocks may not start with zero: 10101 is not 1 followed by 01 x 2.
+        assert_eq!(is_invalid_id(10101), None);
+    }
+
+    #[test]
+    fn test_matches_string_check() {
+        for id in (0..200_000).chain(u64::MAX - 100_000..=u64::MAX) {
+            assert_eq!(is_invalid_id(id), by_string(id), "{}", id);
+        }
+        for id in [11_111_111_111_111_111_111, 18_446_744_071_844_674_407] {
+            assert_eq!(is_invalid_id(id), by_string(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let input = "11\n12\n\n  121212 \nabc\n99999999999999999999\n1010\n";
+        let mut found = Vec::new();
+        let summary = validate(input.as_bytes(), |line, id, r| {
+            found.push((line, id, r.block, r.repeats))
+        })
+        .unwrap();
+        assert_eq!(
+            found,
+            vec![(1, 11, 1, 2), (4, 121212, 12, 3), (7, 1010, 10, 2)]
+        );
+        assert_eq!(
+            summary,
+            Summary {
+                checked: 4,
+                invalid: 3,
+                invalid_sum: 11 + 121212 + 1010,
+                malformed: vec![5, 6],
+            }
+        );
+    }
+}
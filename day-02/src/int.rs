@@ -0,0 +1,407 @@
+//! Integer types the invalid-ID engine can count and sum with.
+//!
+//! The engine finds block bounds by looking at digits, so an integer type
+//! only has to convert to and from digits in a radix and do checked add, sub
+//! and mul. `u64` and `u128` are the fast paths; [`BigUint`] never overflows.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// An arithmetic result did not fit in the chosen integer type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "result does not fit in the chosen integer type")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+pub trait IdInt: Clone + Ord + fmt::Debug + fmt::Display {
+    fn from_u64(n: u64) -> Self;
+
+    /// Builds a number from most-significant-first digits in `radix`,
+    /// or `None` if it does not fit.
+    fn from_digits(digits: &[u8], radix: u32) -> Option<Self>;
+
+    /// Most-significant-first digits in `radix`, `[0]` for zero.
+    fn to_digits(&self, radix: u32) -> Vec<u8>;
+
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    /// Exact halving of an even number.
+    fn half(&self) -> Self;
+
+    fn is_even(&self) -> bool;
+}
+
+macro_rules! impl_id_int {
+    ($t:ty) => {
+        impl IdInt for $t {
+            fn from_u64(n: u64) -> Self {
+                n as $t
+            }
+
+            fn from_digits(digits: &[u8], radix: u32) -> Option<Self> {
+                digits.iter().try_fold(0 as $t, |acc, &d| {
+                    acc.checked_mul(radix as $t)?.checked_add(d as $t)
+                })
+            }
+
+            fn to_digits(&self, radix: u32) -> Vec<u8> {
+                let radix = radix as $t;
+                let mut n = *self;
+                let mut digits = Vec::new();
+                loop {
+                    digits.push((n % radix) as u8);
+                    n /= radix;
+                    if n == 0 {
+                        break;
+                    }
+                }
+                digits.reverse();
+                digits
+            }
+
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *other)
+            }
+
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *other)
+            }
+
+            fn half(&self) -> Self {
+                *self / 2
+            }
+
+            fn is_even(&self) -> bool {
+                self % 2 == 0
+            }
+        }
+    };
+}
+
+impl_id_int!(u64);
+impl_id_int!(u128);
+
+/// Arbitrary-precision unsigned integer for IDs of any length.
+///
+/// Stored as little-endian base-2^32 limbs with no trailing zero limbs,
+/// so zero is the empty vector.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    /// `self * mul + add` for small operands.
+    fn mul_add_small(&mut self, mul: u32, add: u32) {
+        let mut carry = add as u64;
+        for limb in &mut self.limbs {
+            let v = *limb as u64 * mul as u64 + carry;
+            *limb = v as u32;
+            carry = v >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+
+    /// Divides in place by a small divisor and returns the remainder.
+    fn div_rem_small(&mut self, div: u32) -> u32 {
+        let mut rem = 0_u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let v = (rem << 32) | *limb as u64;
+            *limb = (v / div as u64) as u32;
+            rem = v % div as u64;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        rem as u32
+    }
+}
+
+impl IdInt for BigUint {
+    fn from_u64(n: u64) -> Self {
+        BigUint {
+            limbs: vec![n as u32, (n >> 32) as u32],
+        }
+        .normalize()
+    }
+
+    fn from_digits(digits: &[u8], radix: u32) -> Option<Self> {
+        let mut n = BigUint::default();
+        for &d in digits {
+            n.mul_add_small(radix, d as u32);
+        }
+        Some(n)
+    }
+
+    fn to_digits(&self, radix: u32) -> Vec<u8> {
+        let mut n = self.clone();
+        let mut digits = Vec::new();
+        loop {
+            digits.push(n.div_rem_small(radix) as u8);
+            if n.limbs.is_empty() {
+                break;
+            }
+        }
+        digits.reverse();
+        digits
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0_u64;
+        for i in 0..len {
+            let a = *self.limbs.get(i).unwrap_or(&0) as u64;
+            let b = *other.limbs.get(i).unwrap_or(&0) as u64;
+            let v = a + b + carry;
+            limbs.push(v as u32);
+            carry = v >> 32;
+        }
+        limbs.push(carry as u32);
+        Some(BigUint { limbs }.normalize())
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if self < other {
+            return None;
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0_i64;
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let b = *other.limbs.get(i).unwrap_or(&0) as i64;
+            let mut v = a as i64 - b - borrow;
+            borrow = 0;
+            if v < 0 {
+                v += 1 << 32;
+                borrow = 1;
+            }
+            limbs.push(v as u32);
+        }
+        Some(BigUint { limbs }.normalize())
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let mut limbs = vec![0_u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0_u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let v = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = v as u32;
+                carry = v >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        Some(BigUint { limbs }.normalize())
+    }
+
+    fn half(&self) -> Self {
+        let mut n = self.clone();
+        n.div_rem_small(2);
+        n
+    }
+
+    fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|limb| limb % 2 == 0)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "", &format_radix(self, 10))
+    }
+}
+
+/// The digit string was empty or held something other than `0`-`9`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBigUintError;
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid digit string")
+    }
+}
+
+impl std::error::Error for ParseBigUintError {}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_radix(s, 10).ok_or(ParseBigUintError)
+    }
+}
+
+/// Parses `s` as a number in `radix` (2 to 36), accepting `0`-`9` and then
+/// letters in either case. Returns `None` for a bad digit or if it does not fit.
+pub fn parse_radix<T: IdInt>(s: &str, radix: u32) -> Option<T> {
+    if s.is_empty() {
+        return None;
+    }
+    let digits = s
+        .chars()
+        .map(|c| c.to_digit(radix).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    T::from_digits(&digits, radix)
+}
+
+/// Formats `n` in `radix` (2 to 36) with lowercase letters past `9`.
+pub fn format_radix<T: IdInt>(n: &T, radix: u32) -> String {
+    n.to_digits(radix)
+        .iter()
+        .map(|&d| char::from_digit(d as u32, radix).expect("digit below radix"))
+        .collect()
+}
+
+/// Number of decimal digits in `n`, with `0` counted as one digit.
+pub fn digit_len(n: u64) -> usize {
+    n.checked_ilog10().map_or(1, |d| d as usize + 1)
+}
+
+/// `1 + 10^period + 10^(2 * period) + ... + 10^(len - period)`, or `None` if
+/// it does not fit in a `u64` (and so no `len`-digit ID can use it).
+pub fn multiplier(len: usize, period: usize) -> Option<u64> {
+    let base = 10_u64.checked_pow(period as u32)?;
+    let mut multiplier = 0_u64;
+    let mut term = 1_u64;
+    for i in 0..len / period {
+        if i > 0 {
+            term = term.checked_mul(base)?;
+        }
+        multiplier = multiplier.checked_add(term)?;
+    }
+    Some(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(n: u128) -> BigUint {
+        n.to_string().parse().unwrap()
+    }
+
+    #[test]
+    fn test_primitive_overflow_is_reported() {
+        assert_eq!(
+            u64::from_digits(&[1; 20], 10),
+            Some(11_111_111_111_111_111_111)
+        );
+        assert_eq!(u64::from_digits(&[2; 20], 10), None);
+        assert_eq!(u64::from_digits(&[1; 64], 2), Some(u64::MAX));
+        assert_eq!(u64::from_digits(&[1; 65], 2), None);
+        assert_eq!(IdInt::checked_mul(&u64::MAX, &2), None);
+        assert_eq!(u128::from_digits(&[9; 39], 10), None);
+        assert_eq!(0_u64.to_digits(10), vec![0]);
+        assert_eq!(1203_u128.to_digits(10), vec![1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn test_radix_round_trip() {
+        assert_eq!(parse_radix::<u64>("ff", 16), Some(255));
+        assert_eq!(parse_radix::<u64>("FF", 16), Some(255));
+        assert_eq!(parse_radix::<u64>("1010", 2), Some(10));
+        assert_eq!(parse_radix::<u64>("12", 2), None);
+        assert_eq!(parse_radix::<u64>("", 10), None);
+        assert_eq!(parse_radix::<u64>("zz", 36), Some(36 * 36 - 1));
+        assert_eq!(format_radix(&255_u64, 16), "ff");
+        assert_eq!(format_radix(&0_u64, 2), "0");
+        assert_eq!(format_radix(&u128::MAX, 16), "f".repeat(32));
+    }
+
+    #[test]
+    fn test_big_matches_u128() {
+        let values = [
+            0_u128,
+            1,
+            9,
+            10,
+            4_294_967_295,
+            4_294_967_296,
+            u64::MAX as u128,
+            1 << 100,
+        ];
+        for &a in &values {
+            assert_eq!(big(a).to_string(), a.to_string());
+            assert_eq!(big(a).is_even(), a % 2 == 0);
+            assert_eq!(big(a).half(), big(a / 2));
+            for &b in &values {
+                assert_eq!(big(a).cmp(&big(b)), a.cmp(&b));
+                assert_eq!(big(a).checked_add(&big(b)), a.checked_add(b).map(big));
+                assert_eq!(big(a).checked_sub(&big(b)), a.checked_sub(b).map(big));
+                if let Some(p) = a.checked_mul(b) {
+                    assert_eq!(big(a).checked_mul(&big(b)), Some(big(p)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_big_beyond_u128() {
+        let a: BigUint = "123456789012345678901234567890123456789012345"
+            .parse()
+            .unwrap();
+        let b: BigUint = "987654321098765432109876543210".parse().unwrap();
+        assert_eq!(
+            a.checked_mul(&b).unwrap().to_string(),
+            "121932631137021795226185032733744855963374484925817710639370522778615927450"
+        );
+        assert_eq!(
+            a.checked_add(&b).unwrap().to_string(),
+            "123456789012346666555555666655555566665555555"
+        );
+        assert_eq!(
+            parse_radix::<BigUint>(&format_radix(&a, 36), 36),
+            Some(a.clone())
+        );
+        assert_eq!(parse_radix::<BigUint>(&format_radix(&a, 2), 2), Some(a));
+        assert!("12a".parse::<BigUint>().is_err());
+        assert!("".parse::<BigUint>().is_err());
+    }
+
+    #[test]
+    fn test_decimal_helpers() {
+        assert_eq!(digit_len(0), 1);
+        assert_eq!(digit_len(9), 1);
+        assert_eq!(digit_len(10), 2);
+        assert_eq!(digit_len(u64::MAX), 20);
+        assert_eq!(multiplier(6, 2), Some(10101));
+        assert_eq!(multiplier(4, 4), Some(1));
+        assert_eq!(multiplier(20, 1), Some(11_111_111_111_111_111_111));
+        assert_eq!(multiplier(21, 1), None);
+    }
+}
@@ -0,0 +1,216 @@
+//! Lazy, ordered enumeration of the invalid IDs in a range.
+
+use std::iter::FusedIterator;
+
+use crate::order;
+
+/// The invalid IDs in `[left, right]`, in ascending order.
+///
+/// IDs are produced one at a time from the block multipliers, so the range
+/// can hold billions of invalid IDs without any of them being collected.
+/// Iterating from the back yields them in descending order, and `nth` jumps
+/// ahead by rank instead of stepping.
+#[derive(Debug, Clone)]
+pub struct InvalidIds {
+    front: u64,
+    back: u64,
+    exhausted: bool,
+}
+
+impl InvalidIds {
+    pub fn new(left: u64, right: u64) -> Self {
+        InvalidIds {
+            front: left,
+            back: right,
+            exhausted: left > right,
+        }
+    }
+
+    /// Takes `id` from the front, given it is the next ID to yield.
+    fn take_front(&mut self, id: u64) -> u64 {
+        if id == self.back {
+            self.exhausted = true;
+        } else {
+            self.front = id + 1;
+        }
+        id
+    }
+
+    /// Takes `id` from the back, given it is the next ID to yield from there.
+    fn take_back(&mut self, id: u64) -> u64 {
+        if id == self.front {
+            self.exhausted = true;
+        } else {
+            self.back = id - 1;
+        }
+        id
+    }
+
+    fn remaining(&self) -> u64 {
+        if self.exhausted {
+            0
+        } else {
+            order::rank(self.back) - self.front.checked_sub(1).map_or(0, order::rank)
+        }
+    }
+}
+
+impl Iterator for InvalidIds {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.exhausted {
+            return None;
+        }
+        match order::first_at_or_after(self.front) {
+            Some(id) if id <= self.back => Some(self.take_front(id)),
+            _ => {
+                self.exhausted = true;
+                None
+            }
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<u64> {
+        if (n as u64) >= self.remaining() {
+            self.exhausted = true;
+            return None;
+        }
+        let before = self.front.checked_sub(1).map_or(0, order::rank);
+        let id = order::unrank(before + n as u64 + 1).expect("ID within the range");
+        Some(self.take_front(id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        match usize::try_from(remaining) {
+            Ok(n) => (n, Some(n)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+
+    fn count(self) -> usize {
+        self.remaining() as usize
+    }
+
+    fn last(mut self) -> Option<u64> {
+        self.next_back()
+    }
+}
+
+impl DoubleEndedIterator for InvalidIds {
+    fn next_back(&mut self) -> Option<u64> {
+        if self.exhausted {
+            return None;
+        }
+        match order::last_at_or_before(self.back) {
+            Some(id) if id >= self.front => Some(self.take_back(id)),
+            _ => {
+                self.exhausted = true;
+                None
+            }
+        }
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<u64> {
+        if (n as u64) >= self.remaining() {
+            self.exhausted = true;
+            return None;
+        }
+        let id = order::unrank(order::rank(self.back) - n as u64).expect("ID within the range");
+        Some(self.take_back(id))
+    }
+}
+
+impl FusedIterator for InvalidIds {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::is_repeated;
+
+    fn brute(left: u64, right: u64) -> Vec<u64> {
+        (left..=right).filter(|&n| is_repeated(n)).collect()
+    }
+
+    #[test]
+    fn test_example_ranges() {
+        assert_eq!(InvalidIds::new(95, 115).collect::<Vec<_>>(), vec![99, 111]);
+        assert_eq!(
+            InvalidIds::new(998, 1012).collect::<Vec<_>>(),
+            vec![999, 1010]
+        );
+        assert_eq!(InvalidIds::new(1698522, 1698528).next(), None);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for (left, right) in [
+            (1, 150_000),
+            (0, 0),
+            (11, 11),
+            (12, 21),
+            (999_990, 1_010_102),
+        ] {
+            let expected = brute(left, right);
+            assert_eq!(InvalidIds::new(left, right).collect::<Vec<_>>(), expected);
+            let mut reversed: Vec<u64> = InvalidIds::new(left, right).rev().collect();
+            reversed.reverse();
+            assert_eq!(reversed, expected);
+            assert_eq!(InvalidIds::new(left, right).count(), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_nth_and_nth_back() {
+        let expected = brute(1, 150_000);
+        for n in [0, 1, 8, 9, 100, expected.len() - 1] {
+            assert_eq!(InvalidIds::new(1, 150_000).nth(n), Some(expected[n]));
+            assert_eq!(
+                InvalidIds::new(1, 150_000).nth_back(n),
+                Some(expected[expected.len() - 1 - n])
+            );
+        }
+        assert_eq!(InvalidIds::new(1, 150_000).nth(expected.len()), None);
+
+        // Skipping resumes from the right place, from both ends.
+        let mut ids = InvalidIds::new(1, 150_000);
+        assert_eq!(ids.nth(10), Some(expected[10]));
+        assert_eq!(ids.next(), Some(expected[11]));
+        assert_eq!(ids.nth_back(3), Some(expected[expected.len() - 4]));
+        assert_eq!(ids.size_hint().0, expected.len() - 16);
+    }
+
+    #[test]
+    fn test_meeting_in_the_middle() {
+        let mut ids = InvalidIds::new(95, 115);
+        assert_eq!(ids.next(), Some(99));
+        assert_eq!(ids.next_back(), Some(111));
+        assert_eq!(ids.next(), None);
+        assert_eq!(ids.next_back(), None);
+    }
+
+    #[test]
+    fn test_huge_range_is_lazy() {
+        // Billions of invalid IDs; only the first and last few are produced.
+        let mut ids = InvalidIds::new(1, u64::MAX);
+        assert_eq!(ids.next(), Some(11));
+        assert_eq!(ids.next_back(), Some(18_446_744_071_844_674_407));
+        assert!(ids.size_hint().0 > 1_000_000_000);
+        assert_eq!(ids.nth(7), Some(99));
+        assert_eq!(ids.next(), Some(111));
+    }
+
+    #[test]
+    fn test_range_ending_at_u64_max() {
+        // The next block after the largest 20-digit ID would overflow.
+        let last = 18_446_744_071_844_674_407;
+        assert_eq!(InvalidIds::new(last + 1, u64::MAX).next(), None);
+        assert_eq!(InvalidIds::new(last + 1, u64::MAX).next_back(), None);
+        assert_eq!(InvalidIds::new(last + 1, u64::MAX).count(), 0);
+        let mut ids = InvalidIds::new(last, u64::MAX);
+        assert_eq!(ids.next(), Some(last));
+        assert_eq!(ids.next(), None);
+        assert_eq!(InvalidIds::new(last - 1, u64::MAX).next_back(), Some(last));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod check;
+pub mod int;
+pub mod iter;
+pub mod order;
+pub mod pattern;
+pub mod ranges;
+pub mod report;
+pub mod series;
+#[cfg(test)]
+mod testing;
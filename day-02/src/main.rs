@@ -1,136 +1,204 @@
-use std::{collections::HashSet, ops::Div};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    ops::Div,
+};
+
+use day_02::{
+    check,
+    int::{BigUint, IdInt, format_radix},
+    ranges,
+    report::{self, Format},
+    series::{self, Tally},
+};
+
+/// Ranges with more invalid IDs than this only show a count in reports.
+const REPORT_MAX_LISTED: usize = 20;
 
 fn main() {
-    let input = include_str!("../input.txt");
-    println!("Part 1: {}", solve_part1(input));
-    println!("Part 2: {}", solve_part2(input));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        // day-02 --radix 16 serials.txt
+        [flag, radix, path] if flag == "--radix" => {
+            let radix: u32 = radix.parse().expect("Invalid radix");
+            assert!((2..=36).contains(&radix), "Radix must be between 2 and 36");
+            let input = std::fs::read_to_string(path).expect("Cannot read input");
+            let tally = tally_input::<BigUint>(&input, radix);
+            println!(
+                "Invalid IDs: {} (radix {}: {})",
+                tally.count,
+                radix,
+                format_radix(&tally.count, radix)
+            );
+            println!(
+                "Sum:         {} (radix {}: {})",
+                tally.sum,
+                radix,
+                format_radix(&tally.sum, radix)
+            );
+        }
+        // day-02 --fixed serials.txt
+        [flag, path] if flag == "--fixed" => {
+            let input = std::fs::read_to_string(path).expect("Cannot read input");
+            let tally = tally_fixed_input::<BigUint>(&input);
+            println!("Invalid IDs: {}", tally.count);
+            println!("Sum:         {}", tally.sum);
+        }
+        // day-02 --check ids.txt, or - for stdin
+        [flag, path] if flag == "--check" => {
+            let reader: Box<dyn BufRead> = if path == "-" {
+                Box::new(io::stdin().lock())
+            } else {
+                Box::new(BufReader::new(File::open(path).expect("Cannot read input")))
+            };
+            let mut out = BufWriter::new(io::stdout().lock());
+            let summary = check::validate(reader, |line, id, r| {
+                writeln!(out, "line {}: {} ({} x {})", line, id, r.block, r.repeats)
+                    .expect("Cannot write output");
+            })
+            .expect("Cannot read input");
+            drop(out);
+            println!(
+                "Checked {} IDs: {} invalid, summing to {}",
+                summary.checked, summary.invalid, summary.invalid_sum
+            );
+            if !summary.malformed.is_empty() {
+                println!("Malformed lines: {:?}", summary.malformed);
+            }
+        }
+        // day-02 --report text|csv|json
+        [flag, format] if flag == "--report" => {
+            let format: Format = format.parse().unwrap_or_else(|e| panic!("{}", e));
+            let input = include_str!("../input.txt");
+            let ranges = ranges::parse::<u64>(input, 10).unwrap_or_else(|e| panic!("{}", e));
+            let reports = report::build(&ranges, REPORT_MAX_LISTED);
+            print!("{}", report::render(&reports, format));
+        }
+        // day-02 --overlaps
+        [flag] if flag == "--overlaps" => {
+            let input = include_str!("../input.txt");
+            let ranges = ranges::parse::<u64>(input, 10).unwrap_or_else(|e| panic!("{}", e));
+            for (i, j) in ranges::overlaps(&ranges) {
+                println!(
+                    "Range #{} {}-{} overlaps range #{} {}-{}",
+                    i + 1,
+                    ranges[i].0,
+                    ranges[i].1,
+                    j + 1,
+                    ranges[j].0,
+                    ranges[j].1
+                );
+            }
+        }
+        _ => {
+            let input = include_str!("../input.txt");
+            println!("Part 1: {}", solve_part1(input));
+            println!("Part 2: {}", solve_part2(input));
+        }
+    }
 }
 
 fn solve_part1(input: &str) -> u64 {
+    let ranges = ranges::parse::<u64>(input, 10).unwrap_or_else(|e| panic!("{}", e));
+    // Overlapping ranges would count their shared IDs twice.
+    ranges::normalize(&ranges)
+        .into_iter()
+        .map(|(left, right)| sum_doubled(left, right))
+        .sum()
+}
+
+/// Sum of the IDs in `[left, right]` made of a block repeated exactly twice.
+fn sum_doubled(left: u64, right: u64) -> u64 {
     let mut sum_invalid = 0;
-    for range in input.split(',').map(|s| s.trim()) {
-        let (left_str, right_str) = range.split_once('-').unwrap();
-
-        let left = left_str.parse::<u64>().expect("Invalid number");
-        let right = right_str.parse::<u64>().expect("Invalid number");
-        assert!(left <= right, "Invalid range");
-        // assert!(left_str.len() == right_str.len(), "Imbalanced range");
-        // assert!(left_str.len() % 2 == 0, "Imbalanced range");
-
-        let in_range = |n: u64| n >= left && n <= right;
-
-        // Get first few digits of left and right according to special logic:
-        // eg. 1-15 >> 1-1, 95-105 >> 9-10, 890-1450 >> 8-14
-
-        // 1=1, 2=1, 3=1, 4=2, 5=2, 6=3, 7=3, 8=4, 9=4, 10=5, ...
-        let from = left_str[0..left_str.len().div(2).max(1)]
-            .parse::<u64>()
-            .expect("Invalid number");
-        // 1=1, 2=1, 3=2, 4=2, 5=3, 6=3, 7=4, 8=4, 9=5, 10=5, ...
-        let to = right_str[0..right_str.len().div_ceil(2)]
-            .parse::<u64>()
-            .expect("Invalid number");
-
-        // println!("Range {}-{} - seeking {}-{}", left_str, right_str, from, to);
-
-        for i in from..=to {
-            let mul = 10_u64.pow(i.ilog10() + 1);
-            let n = i * mul + i;
-            if n < left {
-                continue;
-            }
-            if n > right {
-                break;
-            }
-            // print!("{} ", n);
-            if in_range(n) {
-                // print!("| {} ", n);
-                sum_invalid += n;
-            }
+    let (left_str, right_str) = (left.to_string(), right.to_string());
+    let in_range = |n: u64| n >= left && n <= right;
+
+    // Get first few digits of left and right according to special logic:
+    // eg. 1-15 >> 1-1, 95-105 >> 9-10, 890-1450 >> 8-14
+
+    // 1=1, 2=1, 3=1, 4=2, 5=2, 6=3, 7=3, 8=4, 9=4, 10=5, ...
+    let from = left_str[0..left_str.len().div(2).max(1)]
+        .parse::<u64>()
+        .expect("Invalid number");
+    // 1=1, 2=1, 3=2, 4=2, 5=3, 6=3, 7=4, 8=4, 9=5, 10=5, ...
+    let to = right_str[0..right_str.len().div_ceil(2)]
+        .parse::<u64>()
+        .expect("Invalid number");
+
+    // println!("Range {}-{} - seeking {}-{}", left_str, right_str, from, to);
+
+    for i in from..=to {
+        let mul = 10_u64.pow(i.ilog10() + 1);
+        let n = i * mul + i;
+        if n < left {
+            continue;
+        }
+        if n > right {
+            break;
+        }
+        // print!("{} ", n);
+        if in_range(n) {
+            // print!("| {} ", n);
+            sum_invalid += n;
         }
-
-        // println!("+ Range {} - {}", range, sum_invalid);
     }
+
+    // println!("+ Range {}-{} - {}", left, right, sum_invalid);
     sum_invalid
 }
 
 fn solve_part2(input: &str) -> u64 {
-    let mut invalid_numbers = HashSet::new();
-    for range in input.split(',').map(|s| s.trim()) {
-        let (left_str, right_str) = range.split_once('-').unwrap();
-
-        let left = left_str.parse::<u64>().expect("Invalid number");
-        let right = right_str.parse::<u64>().expect("Invalid number");
-        assert!(left <= right, "Invalid range");
-
-        // Determine min/max digit lengths of numbers in the range
-        let len_min = left_str.len().min(right_str.len());
-        let len_max = left_str.len().max(right_str.len());
-
-        // For each length L from min to max
-        for l in len_min..=len_max {
-            // Find all divisors k of L where k >= 2 (number of repetitions)
-            let repetitions: Vec<usize> = (2..=l).filter(|k| l % k == 0).collect();
-
-            for k in repetitions {
-                // Pattern length = L / k
-                let pattern_len = l / k;
-
-                // Calculate multiplier using geometric series formula:
-                // multiplier = 1 + 10^pattern_len + 10^(2*pattern_len) + ... + 10^((k-1)*pattern_len)
-                // This equals: (10^(k*pattern_len) - 1) / (10^pattern_len - 1)
-                let base = 10_u64.pow(pattern_len as u32);
-                let multiplier = if base == 1 {
-                    k as u64
-                } else {
-                    (base.pow(k as u32) - 1) / (base - 1)
-                };
-
-                // Calculate valid pattern range
-                // min_pattern = ceil(left / multiplier)
-                let min_pattern = (left + multiplier - 1) / multiplier;
-                // max_pattern = floor(right / multiplier)
-                let max_pattern = right / multiplier;
-
-                // Also need to ensure patterns have exactly pattern_len digits
-                let min_pattern_digits = 10_u64.pow((pattern_len - 1).max(0) as u32);
-                let max_pattern_digits = 10_u64.pow(pattern_len as u32) - 1;
-
-                // Clamp pattern range to valid digit range
-                let pattern_start = min_pattern.max(min_pattern_digits);
-                let pattern_end = max_pattern.min(max_pattern_digits);
-
-                if pattern_start > pattern_end {
-                    continue;
-                }
-
-                // For each pattern in the valid range
-                for pattern in pattern_start..=pattern_end {
-                    let candidate = pattern * multiplier;
-
-                    // Verify it's in [left, right] and has exactly L digits
-                    if candidate < left || candidate > right {
-                        continue;
-                    }
-
-                    // Check that the candidate has exactly L digits
-                    let candidate_digits = candidate.ilog10() + 1;
-                    if candidate_digits != l as u32 {
-                        continue;
-                    }
-
-                    // Verify it's actually a repeated pattern
-                    let pattern_str = pattern.to_string();
-                    let expected = pattern_str.repeat(k);
-                    let candidate_str = candidate.to_string();
-                    if expected == candidate_str {
-                        invalid_numbers.insert(candidate);
-                    }
-                }
-            }
-        }
+    let total = tally_input::<u128>(input, 10).sum;
+    u64::try_from(total).expect("Sum of invalid IDs overflows u64")
+}
+
+/// Counts and sums the Part 2 invalid IDs for ranges written in `radix`.
+fn tally_input<T: IdInt>(input: &str, radix: u32) -> Tally<T> {
+    let ranges = ranges::parse::<T>(input, radix).unwrap_or_else(|e| panic!("{}", e));
+    // An ID inside two overlapping ranges must only be counted once.
+    let merged = ranges::normalize(&ranges);
+
+    let mut total = Tally {
+        count: T::from_u64(0),
+        sum: T::from_u64(0),
+    };
+    for (left, right) in &merged {
+        let tally = series::tally_radix(left, right, radix).expect("Invalid ID total overflows");
+        total.count = total
+            .count
+            .checked_add(&tally.count)
+            .expect("Invalid ID count overflows");
+        total.sum = total
+            .sum
+            .checked_add(&tally.sum)
+            .expect("Invalid ID total overflows");
     }
-    invalid_numbers.iter().sum()
+    total
+}
+
+/// Like [`tally_input`] for zero-padded IDs, where each range's width is
+/// how many digits its bounds are written with.
+fn tally_fixed_input<T: IdInt>(input: &str) -> Tally<T> {
+    let ranges = ranges::parse_fixed::<T>(input, 10).unwrap_or_else(|e| panic!("{}", e));
+    let merged = ranges::normalize_fixed(&ranges);
+
+    let mut total = Tally {
+        count: T::from_u64(0),
+        sum: T::from_u64(0),
+    };
+    for (left, right, width) in &merged {
+        let tally =
+            series::tally_fixed(left, right, *width, 10).expect("Invalid ID total overflows");
+        total.count = total
+            .count
+            .checked_add(&tally.count)
+            .expect("Invalid ID count overflows");
+        total.sum = total
+            .sum
+            .checked_add(&tally.sum)
+            .expect("Invalid ID total overflows");
+    }
+    total
 }
 
 #[cfg(test)]
@@ -207,4 +275,60 @@ mod tests {
 
         assert_eq!(solve_part2(input), 4174379265);
     }
+
+    #[test]
+    fn test_radix_decimal_matches_part2() {
+        let input = include_str!("../input.txt");
+        let tally = tally_input::<BigUint>(input, 10);
+        assert_eq!(tally.sum.to_string(), solve_part2(input).to_string());
+    }
+
+    #[test]
+    fn test_radix_hex_and_binary() {
+        // 0x11, 0x22, ..., 0xff and 0x1010 (0x1111 sits outside the first range)
+        let tally = tally_input::<u64>("a-ff,1000-1010", 16);
+        assert_eq!(tally.count, 16);
+        assert_eq!(tally.sum, (1..=15).map(|d| d * 0x11).sum::<u64>() + 0x1010);
+        // 11, 111, 1010, 1111
+        let tally = tally_input::<u64>("1-1111", 2);
+        assert_eq!(tally.count, 4);
+        assert_eq!(tally.sum, 35);
+    }
+
+    #[test]
+    fn test_part1_overlapping_ranges_count_once() {
+        assert_eq!(solve_part1("11-22,15-33"), 11 + 22 + 33);
+        assert_eq!(solve_part1("95-115,90-120"), 99);
+        assert_eq!(
+            solve_part1("1188511880-1188511890,1188511885-1188511885"),
+            1188511885
+        );
+    }
+
+    #[test]
+    fn test_part1_part2_agree_on_overlaps() {
+        // Every ID here repeats its block exactly twice, so both parts match.
+        let input = "11-22,15-33,1000-1100,1010-1020,1010-1010";
+        assert_eq!(solve_part1(input), solve_part2(input));
+        assert_eq!(solve_part1(input), 11 + 22 + 33 + 1010);
+    }
+
+    #[test]
+    fn test_part2_overlapping_ranges_count_once() {
+        // 99 and 111 sit in both ranges.
+        assert_eq!(solve_part2("95-115,90-120"), 99 + 111);
+        assert_eq!(solve_part2("95-115,95-115"), 99 + 111);
+    }
+
+    #[test]
+    fn test_fixed_width() {
+        // 00120012 is 0012 repeated; read as a number it is not a repetition.
+        let tally = tally_fixed_input::<u64>("00120010-00120020");
+        assert_eq!(tally.count, 1);
+        assert_eq!(tally.sum, 120012);
+        assert_eq!(solve_part2("120010-120020"), 0);
+        // Same width, overlapping: counted once. Different widths stay apart.
+        assert_eq!(tally_fixed_input::<u64>("00-99,50-99").count, 10);
+        assert_eq!(tally_fixed_input::<u64>("00-99,000-999").count, 20);
+    }
 }
@@ -0,0 +1,214 @@
+//! Order statistics over the invalid IDs: rank, unrank and neighbours.
+//!
+//! All queries work from the block multipliers and the closed-form counts in
+//! [`series`], so they take time polylogarithmic in the IDs involved.
+
+use crate::{
+    int::{digit_len, multiplier},
+    series,
+};
+
+/// Longest decimal ID that fits in a `u64`.
+const MAX_LEN: usize = 20;
+
+/// Number of invalid IDs in `[left, right]`.
+fn count_between(left: u64, right: u64) -> u64 {
+    series::count(&left, &right).expect("a count of u64 IDs fits in a u64")
+}
+
+/// Distinct prime factors of `n`.
+fn prime_factors(mut n: usize) -> Vec<usize> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+/// Smallest and largest `len`-digit number.
+fn len_bounds(len: usize) -> (u64, u64) {
+    let max = 10_u64.checked_pow(len as u32).map_or(u64::MAX, |p| p - 1);
+    (10_u64.pow(len as u32 - 1), max)
+}
+
+/// Smallest `len`-digit invalid ID that is `>= n`.
+///
+/// Every invalid ID repeats a block whose length is `len / p` for some prime
+/// `p`, so only those periods need checking.
+fn ceil_in_len(n: u64, len: usize) -> Option<u64> {
+    prime_factors(len)
+        .into_iter()
+        .filter_map(|p| {
+            let period = len / p;
+            let multiplier = multiplier(len, period)?;
+            let (block_min, block_max) = len_bounds(period);
+            let block = n.div_ceil(multiplier).max(block_min);
+            // Past the largest u64 block for this length.
+            if block > block_max {
+                return None;
+            }
+            // A 20-digit ID can still be past `u64::MAX`.
+            block.checked_mul(multiplier)
+        })
+        .min()
+}
+
+/// Largest `len`-digit invalid ID that is `<= n`.
+fn floor_in_len(n: u64, len: usize) -> Option<u64> {
+    prime_factors(len)
+        .into_iter()
+        .filter_map(|p| {
+            let period = len / p;
+            let multiplier = multiplier(len, period)?;
+            let (block_min, block_max) = len_bounds(period);
+            // At most `n / multiplier`, so the product cannot pass `n`.
+            let block = (n / multiplier).min(block_max);
+            (block >= block_min).then(|| block * multiplier)
+        })
+        .max()
+}
+
+/// Smallest invalid ID that is `>= n`.
+pub fn first_at_or_after(n: u64) -> Option<u64> {
+    (digit_len(n)..=MAX_LEN).find_map(|len| {
+        let (len_min, _) = len_bounds(len);
+        ceil_in_len(n.max(len_min), len)
+    })
+}
+
+/// Largest invalid ID that is `<= n`.
+pub fn last_at_or_before(n: u64) -> Option<u64> {
+    (1..=digit_len(n)).rev().find_map(|len| {
+        let (_, len_max) = len_bounds(len);
+        floor_in_len(n.min(len_max), len)
+    })
+}
+
+/// Smallest invalid ID strictly greater than `n`.
+pub fn next_after(n: u64) -> Option<u64> {
+    first_at_or_after(n.checked_add(1)?)
+}
+
+/// Largest invalid ID strictly less than `n`.
+pub fn prev_before(n: u64) -> Option<u64> {
+    last_at_or_before(n.checked_sub(1)?)
+}
+
+/// Smallest valid ID strictly greater than `n`, for handing out replacement
+/// IDs. Two invalid IDs are never adjacent, so this is `n + 1` or `n + 2`.
+pub fn next_valid_after(n: u64) -> Option<u64> {
+    let candidate = n.checked_add(1)?;
+    if first_at_or_after(candidate) == Some(candidate) {
+        candidate.checked_add(1)
+    } else {
+        Some(candidate)
+    }
+}
+
+/// Number of invalid IDs `<= n`.
+pub fn rank(n: u64) -> u64 {
+    count_between(0, n)
+}
+
+/// The `k`-th invalid ID (1-based), so that `rank(unrank(k)) == k`.
+/// Returns `None` for `k == 0` or when fewer than `k` invalid IDs fit in a `u64`.
+pub fn unrank(k: u64) -> Option<u64> {
+    if k == 0 {
+        return None;
+    }
+
+    // Skip whole digit lengths, then binary search inside the right one.
+    let mut k = k;
+    for len in 1..=MAX_LEN {
+        let (len_min, len_max) = len_bounds(len);
+        let in_len = count_between(len_min, len_max);
+        if k > in_len {
+            k -= in_len;
+            continue;
+        }
+
+        let (mut lo, mut hi) = (len_min, len_max);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if count_between(len_min, mid) >= k {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        return Some(lo);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::is_repeated;
+
+    #[test]
+    fn test_rank_unrank_small() {
+        let invalid: Vec<u64> = (0..200_000).filter(|&n| is_repeated(n)).collect();
+        for (i, &id) in invalid.iter().enumerate() {
+            assert_eq!(unrank(i as u64 + 1), Some(id));
+            assert_eq!(rank(id), i as u64 + 1);
+            assert_eq!(rank(id - 1), i as u64);
+        }
+        assert_eq!(unrank(0), None);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        assert_eq!(next_after(0), Some(11));
+        assert_eq!(next_after(11), Some(22));
+        assert_eq!(next_after(99), Some(111));
+        assert_eq!(next_after(1000), Some(1010));
+        assert_eq!(prev_before(11), None);
+        assert_eq!(prev_before(111), Some(99));
+        assert_eq!(prev_before(1010), Some(999));
+        assert_eq!(first_at_or_after(222222), Some(222222));
+        assert_eq!(last_at_or_before(222221), Some(221221));
+
+        let invalid: Vec<u64> = (0..30_000).filter(|&n| is_repeated(n)).collect();
+        for n in 0..20_000 {
+            let next = invalid[invalid.partition_point(|&m| m <= n)];
+            assert_eq!(next_after(n), Some(next), "next_after({})", n);
+            let prev = invalid.partition_point(|&m| m < n).checked_sub(1);
+            assert_eq!(
+                prev_before(n),
+                prev.map(|i| invalid[i]),
+                "prev_before({})",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_valid_after() {
+        assert_eq!(next_valid_after(10), Some(12));
+        assert_eq!(next_valid_after(11), Some(12));
+        assert_eq!(next_valid_after(98), Some(100));
+        assert_eq!(next_valid_after(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_extremes() {
+        let total = rank(u64::MAX);
+        let last = unrank(total).unwrap();
+        assert_eq!(last, 18_446_744_071_844_674_407);
+        assert_eq!(unrank(total + 1), None);
+        assert_eq!(next_after(last), None);
+        assert_eq!(prev_before(u64::MAX), Some(last));
+        assert_eq!(rank(last), total);
+    }
+}
@@ -0,0 +1,468 @@
+//! Families of invalid IDs behind one [`IdPattern`] trait.
+//!
+//! The repeated-block rule from the puzzle is [`Repeated`]; compliance also
+//! flags [`Palindrome`], [`SingleDigit`] and [`ArithmeticDigits`] IDs. Rules
+//! combine with [`IdPattern::or`] and [`IdPattern::and`], and an ID matched
+//! by several rules is still counted once.
+
+use crate::{check, int::digit_len, iter::InvalidIds, series};
+
+pub trait IdPattern {
+    /// Whether `id` belongs to this family.
+    fn contains(&self, id: u64) -> bool;
+
+    /// Members within `[left, right]`, in ascending order.
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_>;
+
+    /// Number of members within `[left, right]`. The default walks
+    /// [`ids`](Self::ids); families with a closed form override it.
+    fn count(&self, left: u64, right: u64) -> u64 {
+        self.ids(left, right).count() as u64
+    }
+
+    /// Sum of the members within `[left, right]`. The default walks
+    /// [`ids`](Self::ids); families with a closed form override it.
+    fn sum(&self, left: u64, right: u64) -> u128 {
+        self.ids(left, right).map(u128::from).sum()
+    }
+
+    /// IDs matched by this rule or `other`.
+    fn or<P: IdPattern>(self, other: P) -> Union<Self, P>
+    where
+        Self: Sized,
+    {
+        Union(self, other)
+    }
+
+    /// IDs matched by both this rule and `other`.
+    fn and<P: IdPattern>(self, other: P) -> Intersection<Self, P>
+    where
+        Self: Sized,
+    {
+        Intersection(self, other)
+    }
+}
+
+impl<P: IdPattern + ?Sized> IdPattern for Box<P> {
+    fn contains(&self, id: u64) -> bool {
+        (**self).contains(id)
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        (**self).ids(left, right)
+    }
+
+    fn count(&self, left: u64, right: u64) -> u64 {
+        (**self).count(left, right)
+    }
+
+    fn sum(&self, left: u64, right: u64) -> u128 {
+        (**self).sum(left, right)
+    }
+}
+
+/// A block of digits repeated at least twice, e.g. `1212` or `111`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Repeated;
+
+impl IdPattern for Repeated {
+    fn contains(&self, id: u64) -> bool {
+        check::is_invalid_id(id).is_some()
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        Box::new(InvalidIds::new(left, right))
+    }
+
+    fn count(&self, left: u64, right: u64) -> u64 {
+        series::count(&left, &right).expect("a count of u64 IDs fits in a u64")
+    }
+
+    fn sum(&self, left: u64, right: u64) -> u128 {
+        series::tally(&(left as u128), &(right as u128))
+            .expect("a sum of u64 IDs fits in a u128")
+            .sum
+    }
+}
+
+/// Reads the same backwards, with at least two digits, e.g. `12321`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Palindrome;
+
+impl Palindrome {
+    /// The `len`-digit palindrome whose first half is `prefix`.
+    fn from_prefix(prefix: u64, len: usize) -> Option<u64> {
+        let mut n = prefix;
+        let mut rest = if len.is_multiple_of(2) {
+            prefix
+        } else {
+            prefix / 10
+        };
+        for _ in 0..len / 2 {
+            n = n.checked_mul(10)?.checked_add(rest % 10)?;
+            rest /= 10;
+        }
+        Some(n)
+    }
+
+    /// First-half prefixes of the `len`-digit palindromes in `[left, right]`.
+    fn prefixes(left: u64, right: u64, len: usize) -> Option<(u64, u64)> {
+        let half = len.div_ceil(2);
+        let shift = 10_u64.pow((len - half) as u32);
+        let mut start = 10_u64.pow(half as u32 - 1);
+        let mut end = 10_u64.pow(half as u32) - 1;
+        if digit_len(left) == len {
+            start = left / shift;
+            if Self::from_prefix(start, len).is_none_or(|p| p < left) {
+                start += 1;
+            }
+        }
+        if digit_len(right) == len {
+            end = right / shift;
+            if Self::from_prefix(end, len).is_none_or(|p| p > right) {
+                end -= 1;
+            }
+        }
+        (start <= end).then_some((start, end))
+    }
+}
+
+/// Sum of digit `j` (counting from the least significant) over `0..=n`.
+fn digit_sum_at(n: u64, j: usize) -> u128 {
+    let place = 10_u128.pow(j as u32);
+    let span = n as u128 + 1;
+    let (cycles, rest) = (span / (10 * place), span % (10 * place));
+    let (full, part) = (rest / place, rest % place);
+    cycles * 45 * place + place * full * full.saturating_sub(1) / 2 + full * part
+}
+
+impl IdPattern for Palindrome {
+    fn contains(&self, id: u64) -> bool {
+        let digits = id.to_string();
+        digits.len() >= 2 && digits.bytes().eq(digits.bytes().rev())
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        if left > right {
+            return Box::new(std::iter::empty());
+        }
+        let lens = digit_len(left).max(2)..=digit_len(right);
+        Box::new(lens.flat_map(move |len| {
+            Self::prefixes(left, right, len)
+                .into_iter()
+                .flat_map(move |(start, end)| {
+                    (start..=end).map_while(move |p| Self::from_prefix(p, len))
+                })
+        }))
+    }
+
+    fn count(&self, left: u64, right: u64) -> u64 {
+        if left > right {
+            return 0;
+        }
+        (digit_len(left).max(2)..=digit_len(right))
+            .filter_map(|len| Self::prefixes(left, right, len))
+            .map(|(start, end)| end - start + 1)
+            .sum()
+    }
+
+    /// The palindrome with prefix `p` is `p` shifted left plus the mirrored
+    /// digits of `p`, so both parts sum in closed form over a prefix range:
+    /// the first as an arithmetic series, the second digit by digit.
+    fn sum(&self, left: u64, right: u64) -> u128 {
+        if left > right {
+            return 0;
+        }
+        (digit_len(left).max(2)..=digit_len(right))
+            .filter_map(|len| Some((len, Self::prefixes(left, right, len)?)))
+            .map(|(len, (start, end))| {
+                let (start, end) = (start as u128, end as u128);
+                let mirrored = len / 2;
+                let skip = len % 2;
+                let shifted = (start + end) * (end - start + 1) / 2 * 10_u128.pow(mirrored as u32);
+                let tail: u128 = (0..mirrored)
+                    .map(|i| {
+                        let digits = digit_sum_at(end as u64, i + skip)
+                            - digit_sum_at(start as u64 - 1, i + skip);
+                        digits * 10_u128.pow((mirrored - 1 - i) as u32)
+                    })
+                    .sum();
+                shifted + tail
+            })
+            .sum()
+    }
+}
+
+/// One digit repeated at least twice, e.g. `7777`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SingleDigit;
+
+impl IdPattern for SingleDigit {
+    fn contains(&self, id: u64) -> bool {
+        let digits = id.to_string();
+        digits.len() >= 2 && digits.bytes().all(|b| b == digits.as_bytes()[0])
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        let repunits = (2..=20).scan(1_u64, |repunit, _| {
+            *repunit = repunit.checked_mul(10)?.checked_add(1)?;
+            Some(*repunit)
+        });
+        Box::new(
+            repunits
+                .flat_map(|repunit| (1..=9).filter_map(move |d| repunit.checked_mul(d)))
+                .skip_while(move |&id| id < left)
+                .take_while(move |&id| id <= right),
+        )
+    }
+}
+
+/// Three or more digits with a constant step between neighbours, e.g.
+/// `1357`, `9630` or `4444`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArithmeticDigits;
+
+impl ArithmeticDigits {
+    /// All `len`-digit members, ascending.
+    fn of_len(len: usize) -> Vec<u64> {
+        let mut ids: Vec<u64> = (1..=9_i64)
+            .flat_map(|first| (-9..=9_i64).map(move |step| (first, step)))
+            .filter(|&(first, step)| (0..=9).contains(&(first + step * (len as i64 - 1))))
+            .filter_map(|(first, step)| {
+                (0..len as i64).try_fold(0_u64, |n, i| {
+                    n.checked_mul(10)?.checked_add((first + step * i) as u64)
+                })
+            })
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl IdPattern for ArithmeticDigits {
+    fn contains(&self, id: u64) -> bool {
+        let digits: Vec<i32> = id.to_string().bytes().map(|b| (b - b'0') as i32).collect();
+        digits.len() >= 3
+            && digits
+                .windows(2)
+                .all(|w| w[1] - w[0] == digits[1] - digits[0])
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        if left > right {
+            return Box::new(std::iter::empty());
+        }
+        let lens = digit_len(left).max(3)..=digit_len(right);
+        Box::new(
+            lens.flat_map(Self::of_len)
+                .skip_while(move |&id| id < left)
+                .take_while(move |&id| id <= right),
+        )
+    }
+}
+
+/// IDs matched by either rule, each once.
+///
+/// Counts and sums are the two rules' own less their [`Intersection`], so
+/// they cost what the intersection does.
+#[derive(Debug, Clone, Copy)]
+pub struct Union<A, B>(pub A, pub B);
+
+impl<A: IdPattern, B: IdPattern> IdPattern for Union<A, B> {
+    fn contains(&self, id: u64) -> bool {
+        self.0.contains(id) || self.1.contains(id)
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        let mut a = self.0.ids(left, right).peekable();
+        let mut b = self.1.ids(left, right).peekable();
+        // Merge the two ascending streams, taking shared IDs once.
+        Box::new(std::iter::from_fn(move || match (a.peek(), b.peek()) {
+            (Some(&x), Some(&y)) if x == y => {
+                b.next();
+                a.next()
+            }
+            (Some(&x), Some(&y)) if x < y => a.next(),
+            (Some(_), Some(_)) => b.next(),
+            (Some(_), None) => a.next(),
+            (None, _) => b.next(),
+        }))
+    }
+
+    fn count(&self, left: u64, right: u64) -> u64 {
+        let shared = Intersection(&self.0, &self.1).count(left, right);
+        self.0.count(left, right) + self.1.count(left, right) - shared
+    }
+
+    fn sum(&self, left: u64, right: u64) -> u128 {
+        let shared = Intersection(&self.0, &self.1).sum(left, right);
+        self.0.sum(left, right) + self.1.sum(left, right) - shared
+    }
+}
+
+/// IDs matched by both rules.
+///
+/// There is no closed form for a generic pair, so listing, counting and
+/// summing walk whichever rule has fewer members in the range and test each
+/// against the other. That is cheap when one side is sparse, e.g.
+/// [`SingleDigit`] or [`ArithmeticDigits`] over all of `u64`, but two dense
+/// rules such as [`Repeated`] and [`Palindrome`] over a range of that size
+/// walk billions of IDs.
+#[derive(Debug, Clone, Copy)]
+pub struct Intersection<A, B>(pub A, pub B);
+
+impl<A: IdPattern, B: IdPattern> IdPattern for Intersection<A, B> {
+    fn contains(&self, id: u64) -> bool {
+        self.0.contains(id) && self.1.contains(id)
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        // Walk whichever side is sparser and test the other.
+        if self.0.count(left, right) <= self.1.count(left, right) {
+            Box::new(self.0.ids(left, right).filter(|&id| self.1.contains(id)))
+        } else {
+            Box::new(self.1.ids(left, right).filter(|&id| self.0.contains(id)))
+        }
+    }
+}
+
+impl<P: IdPattern + ?Sized> IdPattern for &P {
+    fn contains(&self, id: u64) -> bool {
+        (**self).contains(id)
+    }
+
+    fn ids(&self, left: u64, right: u64) -> Box<dyn Iterator<Item = u64> + '_> {
+        (**self).ids(left, right)
+    }
+
+    fn count(&self, left: u64, right: u64) -> u64 {
+        (**self).count(left, right)
+    }
+
+    fn sum(&self, left: u64, right: u64) -> u128 {
+        (**self).sum(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check<P: IdPattern>(pattern: &P, left: u64, right: u64) {
+        let expected: Vec<u64> = (left..=right).filter(|&n| pattern.contains(n)).collect();
+        assert_eq!(pattern.ids(left, right).collect::<Vec<_>>(), expected);
+        assert_eq!(pattern.count(left, right), expected.len() as u64);
+        assert_eq!(
+            pattern.sum(left, right),
+            expected.iter().map(|&n| n as u128).sum::<u128>()
+        );
+    }
+
+    const WINDOWS: &[(u64, u64)] = &[(0, 20_000), (95, 115), (998, 1012), (120_000, 140_000)];
+
+    #[test]
+    fn test_families_match_their_predicate() {
+        for &(left, right) in WINDOWS {
+            check(&Repeated, left, right);
+            check(&Palindrome, left, right);
+            check(&SingleDigit, left, right);
+            check(&ArithmeticDigits, left, right);
+        }
+    }
+
+    #[test]
+    fn test_membership() {
+        assert!(Repeated.contains(121212) && !Repeated.contains(12121));
+        assert!(Palindrome.contains(12121) && !Palindrome.contains(7));
+        assert!(SingleDigit.contains(7777) && !SingleDigit.contains(7));
+        assert!(ArithmeticDigits.contains(1357) && ArithmeticDigits.contains(9630));
+        assert!(!ArithmeticDigits.contains(1358) && !ArithmeticDigits.contains(12));
+    }
+
+    #[test]
+    fn test_combinations_count_once() {
+        let rules = Repeated.or(Palindrome);
+        for &(left, right) in WINDOWS {
+            check(&rules, left, right);
+            check(&Repeated.and(Palindrome), left, right);
+            check(&SingleDigit.or(ArithmeticDigits).or(Repeated), left, right);
+        }
+        // 1111 is repeated, palindromic, a single digit and arithmetic.
+        assert_eq!(rules.ids(1111, 1111).collect::<Vec<_>>(), vec![1111]);
+        assert_eq!(rules.count(1111, 1111), 1);
+        // Single digits are a subset of both repeated patterns and palindromes.
+        assert_eq!(
+            Repeated
+                .and(Palindrome)
+                .and(SingleDigit)
+                .count(1, 1_000_000),
+            SingleDigit.count(1, 1_000_000)
+        );
+    }
+
+    #[test]
+    fn test_rule_sets_at_runtime() {
+        let rules: Vec<Box<dyn IdPattern>> = vec![Box::new(Palindrome), Box::new(SingleDigit)];
+        let union = rules
+            .into_iter()
+            .reduce(|a, b| Box::new(Union(a, b)))
+            .unwrap();
+        check(&union, 0, 5_000);
+    }
+
+    #[test]
+    fn test_large_ranges() {
+        // Palindromes are counted from their prefixes, not enumerated.
+        assert_eq!(Palindrome.count(1, u64::MAX), {
+            let per_len: u64 = (2..=19)
+                .map(|len| 9 * 10_u64.pow((len as u32 - 1) / 2))
+                .sum();
+            per_len + Palindrome.count(10_000_000_000_000_000_000, u64::MAX)
+        });
+        assert_eq!(
+            Palindrome.ids(u64::MAX - 10_000_000_000, u64::MAX).last(),
+            Some(18_446_744_066_044_764_481)
+        );
+        assert_eq!(SingleDigit.count(1, u64::MAX), 9 * 18 + 1);
+    }
+
+    #[test]
+    fn test_palindrome_sum_closed_form() {
+        for &(left, right) in &[
+            (999_999_990_000, 1_000_000_050_000),
+            (123_456_000_000_000, 123_456_000_100_000),
+        ] {
+            let expected: u128 = (left..=right)
+                .filter(|&n| Palindrome.contains(n))
+                .map(u128::from)
+                .sum();
+            assert_eq!(Palindrome.sum(left, right), expected);
+        }
+        // Splitting a huge range anywhere gives the same total.
+        let whole = Palindrome.sum(1, u64::MAX);
+        for split in [10, 99_999, 12_345_678_987_654_321, u64::MAX - 1] {
+            assert_eq!(
+                Palindrome.sum(1, split) + Palindrome.sum(split + 1, u64::MAX),
+                whole
+            );
+        }
+        // 10 to 99: the nine repdigits 11..99 sum to 495.
+        assert_eq!(Palindrome.sum(10, 99), 495);
+    }
+
+    #[test]
+    fn test_combinations_over_u64_with_a_sparse_side() {
+        // Each intersection walks the 163 single-digit IDs, not the billions
+        // of repeated ones or palindromes.
+        let single = SingleDigit.count(1, u64::MAX);
+        assert_eq!(Repeated.and(SingleDigit).count(1, u64::MAX), single);
+        assert_eq!(
+            Repeated.or(SingleDigit).sum(1, u64::MAX),
+            Repeated.sum(1, u64::MAX)
+        );
+        assert_eq!(
+            Palindrome.or(SingleDigit).count(1, u64::MAX),
+            Palindrome.count(1, u64::MAX)
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Parsing, validation and normalization of comma-separated ID ranges.
+//!
+//! Puzzle input ranges may overlap, and counting each range on its own would
+//! count the shared invalid IDs twice. [`normalize`] sorts and merges the
+//! ranges so every ID is covered exactly once; [`overlaps`] says which of the
+//! original ranges collided.
+
+use std::fmt;
+
+use crate::int::{IdInt, parse_radix};
+
+/// A range in the input that could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeError {
+    /// No `-` between the bounds.
+    Malformed { index: usize, text: String },
+    /// A bound is not a number in the radix, or does not fit the integer type.
+    BadNumber { index: usize, text: String },
+    /// The left bound is greater than the right bound.
+    Reversed { index: usize, text: String },
+    /// In fixed-width mode, the bounds are written with different widths.
+    WidthMismatch { index: usize, text: String },
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::Malformed { index, text } => {
+                write!(f, "range #{} `{}` is not of the form a-b", index + 1, text)
+            }
+            RangeError::BadNumber { index, text } => {
+                write!(f, "range #{} `{}` has an invalid bound", index + 1, text)
+            }
+            RangeError::Reversed { index, text } => {
+                write!(f, "range #{} `{}` ends before it starts", index + 1, text)
+            }
+            RangeError::WidthMismatch { index, text } => {
+                write!(
+                    f,
+                    "range #{} `{}` has bounds of different widths",
+                    index + 1,
+                    text
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/// Splits `a-b,c-d,...` and parses both bounds of every range in `radix`,
+/// handing each range to `accept` with its index and text.
+fn parse_with<T: IdInt, R>(
+    input: &str,
+    radix: u32,
+    accept: impl Fn(usize, &str, (&str, &str), (T, T)) -> Result<R, RangeError>,
+) -> Result<Vec<R>, RangeError> {
+    input
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .enumerate()
+        .map(|(index, text)| {
+            let (left_str, right_str) =
+                text.split_once('-').ok_or_else(|| RangeError::Malformed {
+                    index,
+                    text: text.to_string(),
+                })?;
+            let (left_str, right_str) = (left_str.trim(), right_str.trim());
+            let bound = |s: &str| {
+                parse_radix::<T>(s, radix).ok_or_else(|| RangeError::BadNumber {
+                    index,
+                    text: text.to_string(),
+                })
+            };
+            let (left, right) = (bound(left_str)?, bound(right_str)?);
+            if left > right {
+                return Err(RangeError::Reversed {
+                    index,
+                    text: text.to_string(),
+                });
+            }
+            accept(index, text, (left_str, right_str), (left, right))
+        })
+        .collect()
+}
+
+/// Parses `a-b,c-d,...` with bounds in `radix`. Whitespace around ranges is
+/// ignored, and so are empty entries such as a trailing comma.
+pub fn parse<T: IdInt>(input: &str, radix: u32) -> Result<Vec<(T, T)>, RangeError> {
+    parse_with(input, radix, |_, _, _, bounds| Ok(bounds))
+}
+
+/// Parses `a-b,c-d,...` as fixed-width IDs, where the width of each range is
+/// how many digits its bounds are written with: `0012-0099` holds the
+/// 4-digit strings `0012` to `0099`. Both bounds must have the same width.
+pub fn parse_fixed<T: IdInt>(input: &str, radix: u32) -> Result<Vec<(T, T, usize)>, RangeError> {
+    parse_with(
+        input,
+        radix,
+        |index, text, (left_str, right_str), (left, right)| {
+            if left_str.len() != right_str.len() {
+                return Err(RangeError::WidthMismatch {
+                    index,
+                    text: text.to_string(),
+                });
+            }
+            Ok((left, right, left_str.len()))
+        },
+    )
+}
+
+/// Sorts the ranges and merges any that overlap, so that each ID is covered
+/// by at most one of the returned ranges.
+pub fn normalize<T: IdInt>(ranges: &[(T, T)]) -> Vec<(T, T)> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable();
+
+    let mut merged: Vec<(T, T)> = Vec::with_capacity(sorted.len());
+    for (left, right) in sorted {
+        match merged.last_mut() {
+            Some(last) if left <= last.1 => {
+                if right > last.1 {
+                    last.1 = right;
+                }
+            }
+            _ => merged.push((left, right)),
+        }
+    }
+    merged
+}
+
+/// Like [`normalize`] for fixed-width ranges. Ranges of different widths
+/// never merge: `012` and `0012` are different IDs.
+pub fn normalize_fixed<T: IdInt>(ranges: &[(T, T, usize)]) -> Vec<(T, T, usize)> {
+    let mut widths: Vec<usize> = ranges.iter().map(|r| r.2).collect();
+    widths.sort_unstable();
+    widths.dedup();
+    widths
+        .into_iter()
+        .flat_map(|width| {
+            let same: Vec<(T, T)> = ranges
+                .iter()
+                .filter(|r| r.2 == width)
+                .map(|r| (r.0.clone(), r.1.clone()))
+                .collect();
+            normalize(&same)
+                .into_iter()
+                .map(move |(left, right)| (left, right, width))
+        })
+        .collect()
+}
+
+/// Pairs of input positions `(i, j)` with `i < j` whose ranges share at
+/// least one ID, sorted.
+pub fn overlaps<T: IdInt>(ranges: &[(T, T)]) -> Vec<(usize, usize)> {
+    let mut order: Vec<usize> = (0..ranges.len()).collect();
+    order.sort_by(|&a, &b| ranges[a].0.cmp(&ranges[b].0));
+
+    // Sweep by left bound, keeping the ranges that are still open.
+    let mut open: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+    for i in order {
+        let (left, _) = &ranges[i];
+        open.retain(|&j| ranges[j].1 >= *left);
+        pairs.extend(open.iter().map(|&j| (i.min(j), i.max(j))));
+        open.push(i);
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let ranges = parse::<u64>("11-22, 95-115,\n998-1012,\n", 10).unwrap();
+        assert_eq!(ranges, vec![(11, 22), (95, 115), (998, 1012)]);
+        assert_eq!(parse::<u64>("a-ff", 16).unwrap(), vec![(10, 255)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse::<u64>("11-22,95", 10),
+            Err(RangeError::Malformed {
+                index: 1,
+                text: "95".to_string()
+            })
+        );
+        assert_eq!(
+            parse::<u64>("11-2x", 10),
+            Err(RangeError::BadNumber {
+                index: 0,
+                text: "11-2x".to_string()
+            })
+        );
+        assert_eq!(
+            parse::<u64>("1-99999999999999999999", 10),
+            Err(RangeError::BadNumber {
+                index: 0,
+                text: "1-99999999999999999999".to_string()
+            })
+        );
+        assert_eq!(
+            parse::<u64>("22-11", 10).unwrap_err().to_string(),
+            "range #1 `22-11` ends before it starts"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let ranges = [
+            (95_u64, 115),
+            (11, 22),
+            (100, 120),
+            (20, 30),
+            (200, 300),
+            (210, 220),
+        ];
+        assert_eq!(normalize(&ranges), vec![(11, 30), (95, 120), (200, 300)]);
+        assert_eq!(normalize::<u64>(&[]), vec![]);
+        // Touching ranges stay apart; they share no ID.
+        assert_eq!(normalize(&[(1_u64, 5), (6, 9)]), vec![(1, 5), (6, 9)]);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let ranges = [
+            (95_u64, 115),
+            (11, 22),
+            (100, 120),
+            (20, 30),
+            (200, 300),
+            (210, 220),
+        ];
+        assert_eq!(overlaps(&ranges), vec![(0, 2), (1, 3), (4, 5)]);
+        assert_eq!(overlaps(&[(1_u64, 5), (6, 9)]), vec![]);
+        assert_eq!(
+            overlaps(&[(1_u64, 5), (5, 9), (1, 9)]),
+            vec![(0, 1), (0, 2), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_parse_fixed() {
+        assert_eq!(
+            parse_fixed::<u64>("0012-0099, 5-9", 10).unwrap(),
+            vec![(12, 99, 4), (5, 9, 1)]
+        );
+        assert_eq!(
+            parse_fixed::<u64>("5-9,012-99", 10),
+            Err(RangeError::WidthMismatch {
+                index: 1,
+                text: "012-99".to_string()
+            })
+        );
+        assert!(matches!(
+            parse_fixed::<u64>("0012", 10),
+            Err(RangeError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_normalize_fixed() {
+        let ranges = [(10_u64, 20, 4), (15, 30, 4), (10, 20, 3), (0, 5, 4)];
+        assert_eq!(
+            normalize_fixed(&ranges),
+            vec![(10, 20, 3), (0, 5, 4), (10, 30, 4)]
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Per-range audit reports of invalid IDs as a text table, CSV or JSON.
+
+use std::{fmt::Write, str::FromStr};
+
+use crate::{check, iter::InvalidIds, ranges, series};
+
+/// One invalid ID with the block it repeats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub id: u64,
+    /// The shortest block that builds the ID, e.g. `12` for `121212`.
+    pub block: u64,
+    pub repeats: u32,
+}
+
+/// What one input range holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeReport {
+    pub left: u64,
+    pub right: u64,
+    pub count: u64,
+    pub subtotal: u128,
+    /// Every invalid ID in the range, or `None` when there are more than the
+    /// report lists individually.
+    pub findings: Option<Vec<Finding>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown report format `{}`", s)),
+        }
+    }
+}
+
+/// Splits an invalid ID into its shortest repeated block and repeat count.
+fn primitive(id: u64) -> Finding {
+    let repetition = check::is_invalid_id(id).expect("invalid IDs repeat a block");
+    Finding {
+        id,
+        block: repetition.block,
+        repeats: repetition.repeats,
+    }
+}
+
+/// Builds one report per input range, in input order. Ranges with more than
+/// `max_listed` invalid IDs only get a count and subtotal.
+pub fn build(ranges: &[(u64, u64)], max_listed: usize) -> Vec<RangeReport> {
+    ranges
+        .iter()
+        .map(|&(left, right)| {
+            let tally = series::tally(&(left as u128), &(right as u128))
+                .expect("invalid IDs in a u64 range sum within a u128");
+            let findings = (tally.count <= max_listed as u128)
+                .then(|| InvalidIds::new(left, right).map(primitive).collect());
+            RangeReport {
+                left,
+                right,
+                count: tally.count as u64,
+                subtotal: tally.sum,
+                findings,
+            }
+        })
+        .collect()
+}
+
+/// Total over the ranges with each ID counted once, even where ranges overlap.
+fn total(reports: &[RangeReport]) -> (u128, u128) {
+    let bounds: Vec<(u128, u128)> = reports
+        .iter()
+        .map(|r| (r.left as u128, r.right as u128))
+        .collect();
+    ranges::normalize(&bounds)
+        .iter()
+        .map(|(left, right)| series::tally(left, right).expect("fits in a u128"))
+        .fold((0, 0), |(count, sum), t| (count + t.count, sum + t.sum))
+}
+
+pub fn render(reports: &[RangeReport], format: Format) -> String {
+    match format {
+        Format::Text => to_text(reports),
+        Format::Csv => to_csv(reports),
+        Format::Json => to_json(reports),
+    }
+}
+
+pub fn to_text(reports: &[RangeReport]) -> String {
+    let mut out = String::new();
+    let width = reports
+        .iter()
+        .map(|r| format!("{}-{}", r.left, r.right).len())
+        .max()
+        .unwrap_or(0)
+        .max("Range".len());
+
+    writeln!(
+        out,
+        "{:<width$}  {:>6}  {:>24}  Invalid IDs",
+        "Range", "Count", "Subtotal"
+    )
+    .unwrap();
+    for r in reports {
+        let ids = match &r.findings {
+            Some(findings) if findings.is_empty() => "-".to_string(),
+            Some(findings) => findings
+                .iter()
+                .map(|f| format!("{} ({} x {})", f.id, f.block, f.repeats))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => "(too many to list)".to_string(),
+        };
+        let range = format!("{}-{}", r.left, r.right);
+        writeln!(
+            out,
+            "{:<width$}  {:>6}  {:>24}  {}",
+            range, r.count, r.subtotal, ids
+        )
+        .unwrap();
+    }
+    let (count, sum) = total(reports);
+    writeln!(out, "{:<width$}  {:>6}  {:>24}", "Total", count, sum).unwrap();
+    out
+}
+
+/// One row per invalid ID. Ranges whose IDs are not listed, or that have none,
+/// get a single row with the ID columns left empty.
+pub fn to_csv(reports: &[RangeReport]) -> String {
+    let mut out = String::from("left,right,id,block,repeats,range_count,range_subtotal\n");
+    for r in reports {
+        match &r.findings {
+            Some(findings) if !findings.is_empty() => {
+                for f in findings {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{}",
+                        r.left, r.right, f.id, f.block, f.repeats, r.count, r.subtotal
+                    )
+                    .unwrap();
+                }
+            }
+            _ => writeln!(out, "{},{},,,,{},{}", r.left, r.right, r.count, r.subtotal).unwrap(),
+        }
+    }
+    out
+}
+
+/// An array with one object per range; `ids` is `null` when not listed. The
+/// `u128` subtotal is a string, since many JSON readers keep numbers as
+/// doubles and would round it past 2^53.
+pub fn to_json(reports: &[RangeReport]) -> String {
+    let ranges: Vec<String> = reports
+        .iter()
+        .map(|r| {
+            let ids = match &r.findings {
+                Some(findings) => {
+                    let ids: Vec<String> = findings
+                        .iter()
+                        .map(|f| {
+                            format!(
+                                r#"{{"id":{},"block":{},"repeats":{}}}"#,
+                                f.id, f.block, f.repeats
+                            )
+                        })
+                        .collect();
+                    format!("[{}]", ids.join(","))
+                }
+                None => "null".to_string(),
+            };
+            format!(
+                r#"{{"left":{},"right":{},"count":{},"subtotal":"{}","ids":{}}}"#,
+                r.left, r.right, r.count, r.subtotal, ids
+            )
+        })
+        .collect();
+    format!("[{}]\n", ranges.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[(u64, u64)] = &[(11, 22), (95, 115), (998, 1012), (1698522, 1698528)];
+
+    #[test]
+    fn test_primitive() {
+        assert_eq!(
+            primitive(121212),
+            Finding {
+                id: 121212,
+                block: 12,
+                repeats: 3
+            }
+        );
+        assert_eq!(primitive(222222).block, 2);
+        assert_eq!(primitive(222222).repeats, 6);
+        assert_eq!(primitive(1188511885).block, 11885);
+    }
+
+    #[test]
+    fn test_build() {
+        let reports = build(EXAMPLE, 10);
+        assert_eq!(reports[1].count, 2);
+        assert_eq!(reports[1].subtotal, 99 + 111);
+        let ids: Vec<u64> = reports[2]
+            .findings
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|f| f.id)
+            .collect();
+        assert_eq!(ids, vec![999, 1010]);
+        assert_eq!(reports[3].findings, Some(vec![]));
+
+        // Too many to list: the count and subtotal are still exact.
+        let reports = build(&[(1, 1_000_000_000_000)], 10);
+        assert_eq!(reports[0].findings, None);
+        assert_eq!(
+            reports[0].count,
+            series::count(&1_u64, &1_000_000_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let text = to_text(&build(EXAMPLE, 10));
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("Range"));
+        assert!(lines[2].contains("99 (9 x 2), 111 (1 x 3)"));
+        assert!(lines[4].ends_with("  -"));
+        assert!(lines[5].starts_with("Total"));
+        assert!(
+            lines[5]
+                .trim_end()
+                .ends_with(&(11 + 22 + 99 + 111 + 999 + 1010).to_string())
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = to_csv(&build(EXAMPLE, 10));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "left,right,id,block,repeats,range_count,range_subtotal"
+        );
+        assert_eq!(lines[4], "95,115,111,1,3,2,210");
+        assert_eq!(lines[7], "1698522,1698528,,,,0,0");
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[test]
+    fn test_json() {
+        let json = to_json(&build(&[(95, 115), (1, 1_000_000)], 5));
+        assert_eq!(
+            json.trim_end(),
+            r#"[{"left":95,"right":115,"count":2,"subtotal":"210","ids":[{"id":99,"block":9,"repeats":2},{"id":111,"block":1,"repeats":3}]},{"left":1,"right":1000000,"count":1098,"subtotal":"540590850","ids":null}]"#
+        );
+    }
+
+    #[test]
+    fn test_overlapping_total_counts_once() {
+        let text = to_text(&build(&[(95, 115), (90, 120)], 10));
+        let total = text.lines().last().unwrap();
+        assert!(total.trim_end().ends_with("210"), "{}", total);
+    }
+}
@@ -0,0 +1,521 @@
+//! Closed-form counts and sums of invalid (repeated-pattern) IDs.
+//!
+//! An ID with `len` digits is invalid when it is a block of `len / k` digits
+//! repeated `k >= 2` times. Every ID that repeats a `period`-digit block is
+//! `block * multiplier`, where `multiplier = 1 + b^period + ... + b^(len - period)`
+//! for radix `b`, so for one period the invalid IDs inside a range form an
+//! arithmetic series. The first and last block of that series are read
+//! straight off the digits of the range bounds, which keeps the engine
+//! independent of both integer width and radix.
+//!
+//! One ID can repeat blocks of several lengths (`222222` is `2` x 6, `22` x 3
+//! and `222` x 2). Periods `d` and `e` of the same length overlap exactly on
+//! period `gcd(d, e)`, so Möbius inclusion–exclusion over the divisors of `len`
+//! counts each ID once:
+//!
+//! ```text
+//! invalid(len) = -Σ μ(m) · periodic(len / m)   for every m > 1 dividing len
+//! ```
+
+use std::{cmp::Ordering, fmt};
+
+use crate::int::{IdInt, Overflow, format_radix};
+
+/// Number of invalid IDs in a range and their total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tally<T> {
+    pub count: T,
+    pub sum: T,
+}
+
+impl<T: IdInt> Tally<T> {
+    fn zero() -> Self {
+        Tally {
+            count: T::from_u64(0),
+            sum: T::from_u64(0),
+        }
+    }
+
+    fn checked_add(&self, other: &Self) -> Result<Self, Overflow> {
+        Ok(Tally {
+            count: self.count.checked_add(&other.count).ok_or(Overflow)?,
+            sum: self.sum.checked_add(&other.sum).ok_or(Overflow)?,
+        })
+    }
+
+    fn checked_sub(&self, other: &Self) -> Result<Self, Overflow> {
+        Ok(Tally {
+            count: self.count.checked_sub(&other.count).ok_or(Overflow)?,
+            sum: self.sum.checked_sub(&other.sum).ok_or(Overflow)?,
+        })
+    }
+}
+
+/// Möbius function: 0 if `n` has a squared prime factor, otherwise
+/// `(-1)^(number of prime factors)`.
+pub fn mobius(mut n: usize) -> i32 {
+    let mut result = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            result = -result;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        result = -result;
+    }
+    result
+}
+
+/// Compares `block` repeated out to `target.len()` digits with `target`.
+fn cmp_repeated(block: &[u8], target: &[u8]) -> Ordering {
+    target
+        .iter()
+        .enumerate()
+        .map(|(i, t)| block[i % block.len()].cmp(t))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Adds one to a digit string, or returns `false` if it would need another digit.
+fn increment(digits: &mut [u8], radix: u32) -> bool {
+    let top = radix as u8 - 1;
+    for d in digits.iter_mut().rev() {
+        if *d < top {
+            *d += 1;
+            return true;
+        }
+        *d = 0;
+    }
+    false
+}
+
+/// Subtracts one from a digit string, or returns `false` if it is all zeros.
+fn decrement(digits: &mut [u8], radix: u32) -> bool {
+    for d in digits.iter_mut().rev() {
+        if *d > 0 {
+            *d -= 1;
+            return true;
+        }
+        *d = radix as u8 - 1;
+    }
+    false
+}
+
+/// Digits of the multiplier that repeats a `period`-digit block out to `len` digits.
+fn multiplier_digits(len: usize, period: usize) -> Vec<u8> {
+    (0..=len - period)
+        .map(|i| u8::from(i % period == 0))
+        .collect()
+}
+
+/// First and last `period`-digit block whose repetition lies in `[lo, hi]`,
+/// where both bounds have the same number of digits. Leading zeros are fine:
+/// blocks are compared as digit strings.
+fn block_bounds(lo: &[u8], hi: &[u8], period: usize, radix: u32) -> Option<(Vec<u8>, Vec<u8>)> {
+    let mut start = lo[..period].to_vec();
+    if cmp_repeated(&start, lo) == Ordering::Less && !increment(&mut start, radix) {
+        return None;
+    }
+    let mut end = hi[..period].to_vec();
+    if cmp_repeated(&end, hi) == Ordering::Greater && !decrement(&mut end, radix) {
+        return None;
+    }
+    (start <= end).then_some((start, end))
+}
+
+/// IDs in `[lo, hi]` that repeat a `period`-digit block. The sum is only
+/// computed when `with_sum` is set, so counting alone cannot overflow on it.
+fn periodic<T: IdInt>(
+    lo: &[u8],
+    hi: &[u8],
+    period: usize,
+    radix: u32,
+    with_sum: bool,
+) -> Result<Tally<T>, Overflow> {
+    let Some((start, end)) = block_bounds(lo, hi, period, radix) else {
+        return Ok(Tally::zero());
+    };
+    let start = T::from_digits(&start, radix).ok_or(Overflow)?;
+    let end = T::from_digits(&end, radix).ok_or(Overflow)?;
+    let count = end
+        .checked_sub(&start)
+        .and_then(|d| d.checked_add(&T::from_u64(1)))
+        .ok_or(Overflow)?;
+    if !with_sum {
+        return Ok(Tally {
+            count,
+            sum: T::from_u64(0),
+        });
+    }
+
+    // Arithmetic series: multiplier * (start + ... + end). Halve whichever
+    // factor is even before multiplying so nothing exceeds the final sum.
+    let ends = start.checked_add(&end).ok_or(Overflow)?;
+    let block_sum = if count.is_even() {
+        count.half().checked_mul(&ends)
+    } else {
+        ends.half().checked_mul(&count)
+    }
+    .ok_or(Overflow)?;
+    let multiplier = T::from_digits(&multiplier_digits(lo.len(), period), radix).ok_or(Overflow)?;
+    let sum = block_sum.checked_mul(&multiplier).ok_or(Overflow)?;
+    Ok(Tally { count, sum })
+}
+
+/// Invalid IDs in `[lo, hi]`, where both bounds have the same number of digits.
+fn tally_digits<T: IdInt>(
+    lo: &[u8],
+    hi: &[u8],
+    radix: u32,
+    with_sum: bool,
+) -> Result<Tally<T>, Overflow> {
+    let len = lo.len();
+    // Add the mu = -1 terms and subtract the mu = +1 terms, keeping both
+    // parts non-negative for unsigned types.
+    let mut plus = Tally::zero();
+    let mut minus = Tally::zero();
+    for m in (2..=len).filter(|&m| len.is_multiple_of(m)) {
+        match mobius(m) {
+            -1 => plus = plus.checked_add(&periodic(lo, hi, len / m, radix, with_sum)?)?,
+            1 => minus = minus.checked_add(&periodic(lo, hi, len / m, radix, with_sum)?)?,
+            _ => {}
+        }
+    }
+    plus.checked_sub(&minus)
+}
+
+fn tally_with<T: IdInt>(
+    left: &T,
+    right: &T,
+    radix: u32,
+    with_sum: bool,
+) -> Result<Tally<T>, Overflow> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    let mut total = Tally::zero();
+    if left > right {
+        return Ok(total);
+    }
+    let (left, right) = (left.to_digits(radix), right.to_digits(radix));
+    for len in left.len()..=right.len() {
+        let lo = if len == left.len() {
+            left.clone()
+        } else {
+            let mut lo = vec![0; len];
+            lo[0] = 1;
+            lo
+        };
+        let hi = if len == right.len() {
+            right.clone()
+        } else {
+            vec![radix as u8 - 1; len]
+        };
+        total = total.checked_add(&tally_digits(&lo, &hi, radix, with_sum)?)?;
+    }
+    Ok(total)
+}
+
+/// Invalid IDs within `[left, right]`, each counted once. Fails instead of
+/// wrapping if the count or sum does not fit in `T`.
+pub fn tally<T: IdInt>(left: &T, right: &T) -> Result<Tally<T>, Overflow> {
+    tally_with(left, right, 10, true)
+}
+
+/// Number of invalid IDs within `[left, right]`, without summing them.
+pub fn count<T: IdInt>(left: &T, right: &T) -> Result<T, Overflow> {
+    tally_with(left, right, 10, false).map(|t| t.count)
+}
+
+/// Like [`tally`], with repeated patterns made of digits in `radix` (2 to 36):
+/// hex digits for radix 16, bit blocks for radix 2.
+pub fn tally_radix<T: IdInt>(left: &T, right: &T, radix: u32) -> Result<Tally<T>, Overflow> {
+    tally_with(left, right, radix, true)
+}
+
+/// Like [`count`], for repeated patterns in `radix` (2 to 36).
+pub fn count_radix<T: IdInt>(left: &T, right: &T, radix: u32) -> Result<T, Overflow> {
+    tally_with(left, right, radix, false).map(|t| t.count)
+}
+
+/// Why a fixed-width tally could not be computed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixedError {
+    /// A bound has more digits than the width.
+    TooWide {
+        bound: String,
+        width: usize,
+    },
+    Overflow(Overflow),
+}
+
+impl fmt::Display for FixedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedError::TooWide { bound, width } => {
+                write!(f, "{} is wider than {} digits", bound, width)
+            }
+            FixedError::Overflow(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FixedError {}
+
+impl From<Overflow> for FixedError {
+    fn from(e: Overflow) -> Self {
+        FixedError::Overflow(e)
+    }
+}
+
+/// Invalid IDs among the `width`-digit zero-padded strings from `left` to
+/// `right`, so `00120012` counts as `0012` x 2. Every ID in the range is
+/// read at exactly `width` digits; both bounds must fit in that width.
+pub fn tally_fixed<T: IdInt>(
+    left: &T,
+    right: &T,
+    width: usize,
+    radix: u32,
+) -> Result<Tally<T>, FixedError> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    let pad = |n: &T| {
+        let digits = n.to_digits(radix);
+        if digits.len() > width {
+            return Err(FixedError::TooWide {
+                bound: format_radix(n, radix),
+                width,
+            });
+        }
+        let mut padded = vec![0; width - digits.len()];
+        padded.extend(digits);
+        Ok(padded)
+    };
+    let (lo, hi) = (pad(left)?, pad(right)?);
+    if left > right {
+        return Ok(Tally::zero());
+    }
+    Ok(tally_digits(&lo, &hi, radix, true)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        int::{BigUint, parse_radix},
+        testing::{is_repeated, is_repeated_radix, shortest_block},
+    };
+
+    fn brute(left: u64, right: u64) -> Tally<u128> {
+        let ids: Vec<u64> = (left..=right).filter(|&n| is_repeated(n)).collect();
+        Tally {
+            count: ids.len() as u128,
+            sum: ids.iter().map(|&n| n as u128).sum(),
+        }
+    }
+
+    fn full_len(len: u32) -> u64 {
+        count(&10_u64.pow(len - 1), &(10_u64.pow(len) - 1)).unwrap()
+    }
+
+    #[test]
+    fn test_mobius() {
+        let expected = [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0];
+        for (n, &mu) in (1..=12).zip(expected.iter()) {
+            assert_eq!(mobius(n), mu, "mu({})", n);
+        }
+    }
+
+    #[test]
+    fn test_full_lengths() {
+        // 11..99
+        assert_eq!(full_len(2), 9);
+        // abab, which already includes aaaa
+        assert_eq!(full_len(4), 90);
+        // abcabc + ababab - aaaaaa
+        assert_eq!(full_len(6), 981);
+        // 7 is prime: only aaaaaaa
+        assert_eq!(full_len(7), 9);
+    }
+
+    #[test]
+    fn test_tally_matches_brute_force() {
+        for (left, right) in [(1, 200_000), (95, 115), (998, 1012), (222_220, 222_224)] {
+            assert_eq!(
+                tally(&(left as u128), &(right as u128)),
+                Ok(brute(left, right)),
+                "{}-{}",
+                left,
+                right
+            );
+        }
+    }
+
+    #[test]
+    fn test_tally_small_windows() {
+        for left in (1..5_000).step_by(37) {
+            for width in [0, 1, 10, 111, 1_000] {
+                let right = left + width;
+                let expected = brute(left, right);
+                assert_eq!(
+                    tally(&left, &right),
+                    Ok(Tally {
+                        count: expected.count as u64,
+                        sum: expected.sum as u64
+                    }),
+                    "{}-{}",
+                    left,
+                    right
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_u64_edges() {
+        // 20-digit IDs, whose multipliers sit right at the edge of u64.
+        let total = count(&1, &u64::MAX).unwrap();
+        let by_len: u64 = (1..=19).map(full_len).sum::<u64>()
+            + count(&10_000_000_000_000_000_000, &u64::MAX).unwrap();
+        assert_eq!(total, by_len);
+        let (left, right) = (u64::MAX - 5_000, u64::MAX);
+        assert_eq!(
+            tally(&(left as u128), &(right as u128)),
+            Ok(brute(left, right))
+        );
+
+        // The sum of every invalid u64 ID does not fit in a u64, but the count does.
+        assert_eq!(tally(&1, &u64::MAX), Err(Overflow));
+        assert!(tally(&1_u128, &(u64::MAX as u128)).is_ok());
+    }
+
+    #[test]
+    fn test_widths_agree() {
+        for (left, right) in [
+            (1_u64, 10_000_000),
+            (123_456, 98_765_432_123),
+            (1, u64::MAX / 7),
+        ] {
+            let narrow = tally(&(left as u128), &(right as u128)).unwrap();
+            let wide = tally(&BigUint::from_u64(left), &BigUint::from_u64(right)).unwrap();
+            assert_eq!(wide.count.to_string(), narrow.count.to_string());
+            assert_eq!(wide.sum.to_string(), narrow.sum.to_string());
+        }
+    }
+
+    #[test]
+    fn test_radix_matches_brute_force() {
+        for radix in [2, 3, 7, 16, 36] {
+            for (left, right) in [(0, 5_000), (1_234, 70_000)] {
+                let ids: Vec<u64> = (left..=right)
+                    .filter(|&n| is_repeated_radix(n, radix))
+                    .collect();
+                let expected = Tally {
+                    count: ids.len() as u64,
+                    sum: ids.iter().sum(),
+                };
+                assert_eq!(
+                    tally_radix(&left, &right, radix),
+                    Ok(expected),
+                    "radix {} {}-{}",
+                    radix,
+                    left,
+                    right
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_radix_examples() {
+        // Binary 11, 111, 1010, 1111
+        assert_eq!(
+            tally_radix(&1_u64, &15, 2),
+            Ok(Tally {
+                count: 4,
+                sum: 3 + 7 + 10 + 15
+            })
+        );
+        // Hex 11 through ff in steps of 0x11
+        let left = parse_radix::<u64>("a", 16).unwrap();
+        let right = parse_radix::<u64>("ff", 16).unwrap();
+        assert_eq!(count_radix(&left, &right, 16), Ok(15));
+        // A 128-bit serial made of eight repeated 16-bit blocks.
+        let id = parse_radix::<u128>(&"dead".repeat(8), 16).unwrap();
+        assert_eq!(count_radix(&id, &id, 16), Ok(1));
+        assert_eq!(count_radix(&(id - 1), &(id - 1), 16), Ok(0));
+        // Decimal mode matches the decimal entry points.
+        assert_eq!(tally_radix(&95_u64, &115, 10), tally(&95_u64, &115));
+    }
+
+    #[test]
+    fn test_beyond_u128() {
+        // Range covering all 40-digit IDs: 10^39 .. 10^40 - 1.
+        let left: BigUint = format!("1{}", "0".repeat(39)).parse().unwrap();
+        let right: BigUint = "9".repeat(40).parse().unwrap();
+        let t = tally(&left, &right).unwrap();
+        // mu(2) = mu(5) = -1 and mu(10) = 1, the rest vanish: P(20) + P(8) - P(4)
+        let expected = 9 * 10_u128.pow(19) + 9 * 10_u128.pow(7) - 9 * 10_u128.pow(3);
+        assert_eq!(t.count.to_string(), expected.to_string());
+
+        // A 40-digit window holding exactly one invalid ID.
+        let id = "12".repeat(20);
+        let left: BigUint = id.parse().unwrap();
+        let t = tally(&left, &left).unwrap();
+        assert_eq!(t.count.to_string(), "1");
+        assert_eq!(t.sum.to_string(), id);
+    }
+
+    #[test]
+    fn test_fixed_width_matches_brute_force() {
+        let is_repeated_padded =
+            |n: u64, width: usize| shortest_block(&format!("{:0width$}", n)).is_some();
+        for (left, right, width) in [(0, 9_999, 4), (0, 999_999, 6), (115, 12_345, 8), (5, 5, 1)] {
+            let ids: Vec<u64> = (left..=right)
+                .filter(|&n| is_repeated_padded(n, width))
+                .collect();
+            assert_eq!(
+                tally_fixed(&(left as u128), &(right as u128), width, 10),
+                Ok(Tally {
+                    count: ids.len() as u128,
+                    sum: ids.iter().map(|&n| n as u128).sum(),
+                }),
+                "{}-{} at width {}",
+                left,
+                right,
+                width
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixed_width_sees_leading_zeros() {
+        // 00120012 is 0012 x 2, and 00000000 is 0 x 8.
+        let t = tally_fixed(&120012_u64, &120012, 8, 10).unwrap();
+        assert_eq!((t.count, t.sum), (1, 120012));
+        assert_eq!(tally_fixed(&0_u64, &0, 8, 10).unwrap().count, 1);
+        // Unpadded, 120012 is not a repetition.
+        assert_eq!(count(&120012_u64, &120012), Ok(0));
+        // At width 4, 0..=9999 behaves like all 4-digit strings: 10^2 blocks.
+        assert_eq!(tally_fixed(&0_u64, &9999, 4, 10).unwrap().count, 100);
+        // Binary strings of width 4 made of a repeated 2-bit block.
+        assert_eq!(tally_fixed(&0_u64, &15, 4, 2).unwrap().count, 4);
+    }
+
+    #[test]
+    fn test_fixed_width_rejects_wide_bounds() {
+        let err = tally_fixed(&5_u64, &12345, 4, 10).unwrap_err();
+        assert_eq!(
+            err,
+            FixedError::TooWide {
+                bound: "12345".to_string(),
+                width: 4
+            }
+        );
+        assert_eq!(err.to_string(), "12345 is wider than 4 digits");
+        assert!(tally_fixed(&255_u64, &0, 1, 16).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! Brute-force oracles shared by the unit tests.
+
+use crate::int::format_radix;
+
+/// Length of the shortest block that, written at least twice, spells `s`.
+pub fn shortest_block(s: &str) -> Option<usize> {
+    (1..s.len())
+        .filter(|&p| s.len().is_multiple_of(p))
+        .find(|&p| s[..p].repeat(s.len() / p) == s)
+}
+
+/// Whether `n` is a block of decimal digits repeated at least twice.
+pub fn is_repeated(n: u64) -> bool {
+    shortest_block(&n.to_string()).is_some()
+}
+
+/// Like [`is_repeated`] with `n` written in `radix`.
+pub fn is_repeated_radix(n: u64, radix: u32) -> bool {
+    shortest_block(&format_radix(&n, radix)).is_some()
+}
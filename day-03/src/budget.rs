@@ -0,0 +1,313 @@
+//! Battery selection when every battery has an activation cost and the total
+//! cost must stay within a budget.
+//!
+//! The joltage is still fixed one digit at a time, largest first, but the
+//! position a digit is taken from now matters: an earlier copy leaves more
+//! batteries to choose from, a cheaper one leaves more budget. So each step
+//! keeps, for every possible next position, the least cost spent to get
+//! there, and a suffix table of the cheapest way to finish says which digits
+//! can still lead to a full selection. The result is exact.
+
+use crate::joltage::{Selection, in_radix};
+
+/// How many batteries to turn on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Count {
+    Exactly(usize),
+    AtMost(usize),
+}
+
+/// `table[i][l]`: least cost of `l` batteries at positions `i..`, or `None`
+/// if fewer than `l` remain.
+fn min_costs(costs: &[u64], k: usize) -> Vec<Vec<Option<u64>>> {
+    let n = costs.len();
+    let mut table = vec![vec![None; k + 1]; n + 1];
+    table[n][0] = Some(0);
+    for i in (0..n).rev() {
+        table[i][0] = Some(0);
+        for l in 1..=k {
+            let take = table[i + 1][l - 1].map(|c: u64| c.saturating_add(costs[i]));
+            table[i][l] = match (table[i + 1][l], take) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+        }
+    }
+    table
+}
+
+/// Largest joltage from exactly `k` batteries costing at most `budget`, given
+/// the [`min_costs`] table for at least `k` batteries.
+fn select_exactly(
+    bank: &[u32],
+    costs: &[u64],
+    finish: &[Vec<Option<u64>>],
+    k: usize,
+    budget: u64,
+) -> Option<Selection> {
+    let n = bank.len();
+    if finish[0][k].is_none_or(|c| c > budget) {
+        return None;
+    }
+
+    // spent[i]: least cost of the digits chosen so far, with position `i`
+    // the first still free. from[step][i] is the state the step came from.
+    let mut spent: Vec<Option<u64>> = vec![None; n + 1];
+    spent[0] = Some(0);
+    let mut from: Vec<Vec<usize>> = Vec::with_capacity(k);
+    for left in (1..=k).rev() {
+        // cheapest[p]: the cheapest state that may take position p next.
+        let mut cheapest: Vec<Option<(u64, usize)>> = vec![None; n];
+        let mut running: Option<(u64, usize)> = None;
+        for p in 0..n {
+            if let Some(cost) = spent[p]
+                && running.is_none_or(|(c, _)| cost < c)
+            {
+                running = Some((cost, p));
+            }
+            cheapest[p] = running;
+        }
+        // Cost after taking position p, if a full selection can still follow.
+        let take = |p: usize| {
+            let (before, state) = cheapest[p]?;
+            let after = before.checked_add(costs[p])?;
+            let total = after.checked_add(finish[p + 1][left - 1]?)?;
+            (total <= budget).then_some((after, state))
+        };
+        let digit = (0..10)
+            .rev()
+            .find(|&d| (0..n).any(|p| bank[p] == d && take(p).is_some()))
+            .expect("the finish table says a selection exists");
+
+        let mut next = vec![None; n + 1];
+        let mut came_from = vec![0; n + 1];
+        for p in (0..n).filter(|&p| bank[p] == digit) {
+            if let Some((after, state)) = take(p) {
+                next[p + 1] = Some(after);
+                came_from[p + 1] = state;
+            }
+        }
+        from.push(came_from);
+        spent = next;
+    }
+
+    let mut at = (0..=n)
+        .filter(|&i| spent[i].is_some())
+        .min_by_key(|&i| spent[i])
+        .expect("a state survives");
+    let mut indices = vec![0; k];
+    for step in (0..k).rev() {
+        indices[step] = at - 1;
+        at = from[step][at];
+    }
+    Some(Selection::from_indices(bank, indices))
+}
+
+/// Most batteries, up to `k`, that fit in `budget` with a non-zero first
+/// digit: a joltage's value only grows with its significant digits, and
+/// dropping a leading zero never costs more.
+fn most_significant(
+    bank: &[u32],
+    costs: &[u64],
+    finish: &[Vec<Option<u64>>],
+    k: usize,
+    budget: u64,
+) -> usize {
+    (0..bank.len())
+        .filter(|&p| bank[p] != 0 && costs[p] <= budget)
+        .filter_map(|p| {
+            let left = budget - costs[p];
+            // Affordability only shrinks with more batteries.
+            (0..k)
+                .rev()
+                .find(|&l| finish[p + 1][l].is_some_and(|c| c <= left))
+        })
+        .map(|rest| rest + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Largest joltage from `count` batteries of `bank` whose `costs` add up to
+/// at most `budget`, or `None` if no such selection exists or a digit is not
+/// decimal. With [`Count::AtMost`] turning nothing on is always allowed.
+pub fn select_within_budget(
+    bank: &[u32],
+    costs: &[u64],
+    count: Count,
+    budget: u64,
+) -> Option<Selection> {
+    assert_eq!(bank.len(), costs.len(), "one cost per battery");
+    if !in_radix(bank, 10) {
+        return None;
+    }
+    match count {
+        Count::Exactly(k) => select_exactly(bank, costs, &min_costs(costs, k), k, budget),
+        Count::AtMost(k) => {
+            // One table serves every length; only the longest selection with
+            // a non-zero lead can be the largest.
+            let k = k.min(bank.len());
+            let finish = min_costs(costs, k);
+            let len = most_significant(bank, costs, &finish, k, budget);
+            select_exactly(bank, costs, &finish, len, budget)
+        }
+    }
+}
+
+/// A bank with the activation cost of each of its batteries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostedBank {
+    pub bank: Vec<u32>,
+    pub costs: Vec<u64>,
+}
+
+/// Reads banks that each come with a cost row on the following line, costs
+/// separated by spaces or commas. Blank lines are skipped.
+pub fn parse_costed(input: &str) -> Result<Vec<CostedBank>, String> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let mut banks = Vec::new();
+    while let Some((i, bank_line)) = lines.next() {
+        let bank = crate::joltage::parse_bank(bank_line);
+        let (j, cost_line) = lines
+            .next()
+            .ok_or_else(|| format!("line {}: bank has no cost row", i + 1))?;
+        let costs = cost_line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                t.parse::<u64>()
+                    .map_err(|_| format!("line {}: `{}` is not a cost", j + 1, t))
+            })
+            .collect::<Result<Vec<u64>, String>>()?;
+        if costs.len() != bank.len() {
+            return Err(format!(
+                "line {}: {} costs for {} batteries",
+                j + 1,
+                costs.len(),
+                bank.len()
+            ));
+        }
+        banks.push(CostedBank { bank, costs });
+    }
+    Ok(banks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        joltage::{parse_bank, select},
+        testing::{digits_at, subsets, xorshift},
+    };
+
+    /// Numeric order of two joltages given as digit strings.
+    fn numeric_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+        let a = a.trim_start_matches('0');
+        let b = b.trim_start_matches('0');
+        a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+    }
+
+    fn brute(bank: &[u32], costs: &[u64], count: Count, budget: u64) -> Option<String> {
+        let lengths = match count {
+            Count::Exactly(k) => k..=k,
+            Count::AtMost(k) => 0..=k,
+        };
+        lengths
+            .flat_map(|k| subsets(bank.len(), k))
+            .filter(|idx| idx.iter().map(|&i| costs[i]).sum::<u64>() <= budget)
+            .map(|idx| digits_at(bank, &idx))
+            .max_by(|a, b| numeric_cmp(a, b))
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for state in xorshift(0xc057).take(300) {
+            let n = (state % 11) as usize;
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (4 * i)) % 10) as u32).collect();
+            let costs: Vec<u64> = (0..n).map(|i| (state >> (3 * i + 20)) % 5).collect();
+            let budget = (state >> 58) % 12;
+            for k in 0..=n + 1 {
+                for count in [Count::Exactly(k), Count::AtMost(k)] {
+                    let got = select_within_budget(&bank, &costs, count, budget);
+                    let expected = brute(&bank, &costs, count, budget);
+                    assert_eq!(
+                        got.as_ref()
+                            .map(|s| s.digits.trim_start_matches('0').to_string()),
+                        expected.map(|s| s.trim_start_matches('0').to_string()),
+                        "{:?} {:?} {:?} budget={}",
+                        bank,
+                        costs,
+                        count,
+                        budget
+                    );
+                    if let (Some(selection), Count::Exactly(k)) = (got, count) {
+                        assert_eq!(selection.indices.len(), k);
+                        let cost: u64 = selection.indices.iter().map(|&i| costs[i]).sum();
+                        assert!(cost <= budget);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unlimited_budget_is_the_greedy() {
+        let bank = parse_bank("818181911112111");
+        let costs = vec![3; bank.len()];
+        for k in 0..=bank.len() {
+            let budget = select_within_budget(&bank, &costs, Count::Exactly(k), u64::MAX);
+            assert_eq!(budget.map(|s| s.digits), select(&bank, k).map(|s| s.digits));
+        }
+    }
+
+    #[test]
+    fn test_budget_steers_the_choice() {
+        // The 9 is too expensive, so the best pair skips it.
+        let bank = parse_bank("1928");
+        let costs = [1, 10, 1, 1];
+        let selection = select_within_budget(&bank, &costs, Count::Exactly(2), 5).unwrap();
+        assert_eq!(selection.digits, "28");
+        assert_eq!(selection.indices, vec![2, 3]);
+        assert_eq!(
+            select_within_budget(&bank, &costs, Count::Exactly(4), 12),
+            None
+        );
+        // At most 3 within 2: only two batteries are affordable.
+        let selection = select_within_budget(&bank, &costs, Count::AtMost(3), 2).unwrap();
+        assert_eq!(selection.digits, "28");
+        // Only decimal digits are searched.
+        assert_eq!(
+            select_within_budget(&[12], &[1], Count::Exactly(1), 5),
+            None
+        );
+        assert_eq!(select_within_budget(&[12], &[1], Count::AtMost(1), 5), None);
+    }
+
+    #[test]
+    fn test_parse_costed() {
+        let banks = parse_costed("1928\n1 10, 1 1\n\n55\n2 2\n").unwrap();
+        assert_eq!(
+            banks,
+            vec![
+                CostedBank {
+                    bank: vec![1, 9, 2, 8],
+                    costs: vec![1, 10, 1, 1]
+                },
+                CostedBank {
+                    bank: vec![5, 5],
+                    costs: vec![2, 2]
+                },
+            ]
+        );
+        assert_eq!(
+            parse_costed("12\n1\n"),
+            Err("line 2: 1 costs for 2 batteries".to_string())
+        );
+        assert_eq!(
+            parse_costed("12\n"),
+            Err("line 1: bank has no cost row".to_string())
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Battery selection under thermal limits: a minimum gap between chosen
+//! batteries and positions that must stay off.
+//!
+//! The greedy still fixes the joltage one digit at a time, taking the largest
+//! digit that leaves room for the rest. "Room" is a suffix table of how many
+//! batteries can still be turned on from each position under the limits.
+
+use std::fmt;
+
+use crate::joltage::Selection;
+
+/// Limits on which batteries may be turned on together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    /// Chosen batteries must be at least this many positions apart: 1 allows
+    /// neighbours, 2 forbids two neighbours both being on.
+    pub min_gap: usize,
+    /// Positions that must stay off. Positions past the end of the bank are
+    /// ignored.
+    pub forbidden: Vec<usize>,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Constraints {
+            min_gap: 1,
+            forbidden: Vec::new(),
+        }
+    }
+}
+
+/// No selection of the requested size meets the constraints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Infeasible {
+    pub requested: usize,
+    /// The most batteries the constraints allow at once.
+    pub possible: usize,
+}
+
+impl fmt::Display for Infeasible {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot turn on {} batteries, the constraints allow at most {}",
+            self.requested, self.possible
+        )
+    }
+}
+
+impl std::error::Error for Infeasible {}
+
+/// The largest joltage from exactly `k` batteries of `bank` that meet
+/// `constraints`, or how many batteries could be turned on at most. Panics on
+/// a digit above 9.
+pub fn select_constrained(
+    bank: &[u32],
+    k: usize,
+    constraints: &Constraints,
+) -> Result<Selection, Infeasible> {
+    let n = bank.len();
+    // Any gap past the end of the bank allows one battery, like `n + 1`.
+    let gap = constraints.min_gap.clamp(1, n + 1);
+    let mut allowed = vec![true; n];
+    for &p in constraints.forbidden.iter().filter(|&&p| p < n) {
+        allowed[p] = false;
+    }
+
+    // room[i]: most batteries that can be on using positions i.. only.
+    let mut room = vec![0; n + gap];
+    for i in (0..n).rev() {
+        room[i] = room[i + 1];
+        if allowed[i] {
+            room[i] = room[i].max(1 + room[i + gap]);
+        }
+    }
+    if room[0] < k {
+        return Err(Infeasible {
+            requested: k,
+            possible: room[0],
+        });
+    }
+
+    let mut indices = Vec::with_capacity(k);
+    let mut pos = 0;
+    for left in (1..=k).rev() {
+        // Room only shrinks further right, so the leftmost of equal digits
+        // is always at least as good.
+        let mut best: Option<usize> = None;
+        for p in pos..n {
+            if room[p] < left {
+                break;
+            }
+            if allowed[p] && 1 + room[p + gap] >= left && best.is_none_or(|b| bank[p] > bank[b]) {
+                best = Some(p);
+            }
+        }
+        let p = best.expect("room says a battery fits");
+        indices.push(p);
+        pos = p + gap;
+    }
+    Ok(Selection::from_indices(bank, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        joltage::{parse_bank, select},
+        testing::{digits_at, subsets, xorshift},
+    };
+
+    fn brute(bank: &[u32], k: usize, constraints: &Constraints) -> Option<String> {
+        let gap = constraints.min_gap.max(1);
+        subsets(bank.len(), k)
+            .filter(|idx| idx.windows(2).all(|w| w[1] - w[0] >= gap))
+            .filter(|idx| idx.iter().all(|i| !constraints.forbidden.contains(i)))
+            .map(|idx| digits_at(bank, &idx))
+            .max()
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for state in xorshift(0xdead_beef).take(300) {
+            let n = (state % 12) as usize;
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (4 * i)) % 10) as u32).collect();
+            let constraints = Constraints {
+                min_gap: (state >> 50) as usize % 4,
+                forbidden: (0..n).filter(|i| (state >> (40 + i)) & 3 == 0).collect(),
+            };
+            for k in 0..=n {
+                let got = select_constrained(&bank, k, &constraints);
+                assert_eq!(
+                    got.as_ref().ok().map(|s| s.digits.clone()),
+                    brute(&bank, k, &constraints),
+                    "{:?} k={} {:?}",
+                    bank,
+                    k,
+                    constraints
+                );
+                if let Ok(selection) = got {
+                    let gap = constraints.min_gap.max(1);
+                    assert!(selection.indices.windows(2).all(|w| w[1] - w[0] >= gap));
+                    assert!(
+                        selection
+                            .indices
+                            .iter()
+                            .all(|i| !constraints.forbidden.contains(i))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_matches_select() {
+        let bank = parse_bank("818181911112111");
+        for k in 0..=bank.len() {
+            assert_eq!(
+                select_constrained(&bank, k, &Constraints::default()).ok(),
+                select(&bank, k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_gap_wider_than_bank() {
+        let bank = parse_bank("18239");
+        for min_gap in [bank.len(), bank.len() + 1, 1 << 40, usize::MAX] {
+            let constraints = Constraints {
+                min_gap,
+                forbidden: vec![],
+            };
+            let selection = select_constrained(&bank, 1, &constraints).unwrap();
+            assert_eq!(selection.digits, "9");
+            assert_eq!(
+                select_constrained(&bank, 2, &constraints),
+                Err(Infeasible {
+                    requested: 2,
+                    possible: 1
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_neighbours() {
+        let bank = parse_bank("99919");
+        let constraints = Constraints {
+            min_gap: 2,
+            forbidden: vec![],
+        };
+        let selection = select_constrained(&bank, 2, &constraints).unwrap();
+        assert_eq!(selection.digits, "99");
+        assert_eq!(selection.indices, vec![0, 2]);
+        assert_eq!(
+            select_constrained(&bank, 4, &constraints),
+            Err(Infeasible {
+                requested: 4,
+                possible: 3
+            })
+        );
+        let forbidden = Constraints {
+            min_gap: 2,
+            forbidden: vec![0, 2, 99],
+        };
+        assert_eq!(
+            select_constrained(&bank, 2, &forbidden).unwrap().indices,
+            vec![1, 4]
+        );
+        assert_eq!(
+            select_constrained(&bank, 3, &forbidden)
+                .unwrap_err()
+                .to_string(),
+            "cannot turn on 3 batteries, the constraints allow at most 2"
+        );
+    }
+}
@@ -0,0 +1,556 @@
+//! Picking `k` batteries out of a bank to get the largest joltage.
+//!
+//! The joltage of a selection is its digits read in bank order, so the best
+//! selection is the lexicographically largest subsequence of length `k`. A
+//! monotonic stack finds it in one pass: a digit evicts smaller digits before
+//! it while there are still `n - k` to drop. The smallest joltage is the same
+//! pass with larger digits evicted instead, and the `r`-th largest walks the
+//! digits from the top using counts of distinct subsequences.
+
+use crate::{optimal::next_occurrences, total::DecimalSum};
+
+/// Battery digits of one bank line. Anything that is not a decimal digit is
+/// skipped.
+pub fn parse_bank(line: &str) -> Vec<u32> {
+    parse_bank_radix(line, 10)
+}
+
+/// Battery digits of one bank line in `radix` (2 to 36), e.g. `0`-`9` and
+/// `a`-`f` in either case for hex. Anything else is skipped.
+pub fn parse_bank_radix(line: &str, radix: u32) -> Vec<u32> {
+    line.chars().filter_map(|c| c.to_digit(radix)).collect()
+}
+
+/// The batteries picked for a bank and the joltage they give.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Positions of the chosen batteries in the bank, ascending.
+    pub indices: Vec<usize>,
+    /// The joltage as digits in `radix`, exact for any number of batteries.
+    pub digits: String,
+    /// The radix of the bank, 10 unless chosen with [`select_radix`].
+    pub radix: u32,
+}
+
+impl Selection {
+    pub(crate) fn from_indices(bank: &[u32], indices: Vec<usize>) -> Self {
+        Self::from_indices_radix(bank, indices, 10)
+    }
+
+    /// Panics unless every chosen digit is below `radix`; the selections
+    /// check their banks with [`in_radix`] first.
+    pub(crate) fn from_indices_radix(bank: &[u32], indices: Vec<usize>, radix: u32) -> Self {
+        Selection {
+            digits: indices
+                .iter()
+                .map(|&i| char::from_digit(bank[i], radix).expect("battery digit"))
+                .collect(),
+            indices,
+            radix,
+        }
+    }
+
+    /// The joltage as a number, or `None` if it does not fit in a `u64` or
+    /// the selection is not decimal. Use [`decimal_joltage`] for other radixes.
+    ///
+    /// [`decimal_joltage`]: Selection::decimal_joltage
+    pub fn joltage(&self) -> Option<u64> {
+        if self.radix != 10 {
+            return None;
+        }
+        self.digits.bytes().try_fold(0_u64, |acc, b| {
+            acc.checked_mul(10)?.checked_add((b - b'0') as u64)
+        })
+    }
+
+    /// The joltage converted to decimal, exact whatever the radix and length.
+    pub fn decimal_joltage(&self) -> DecimalSum {
+        DecimalSum::from_radix(&self.digits, self.radix).expect("digits in radix")
+    }
+}
+
+/// Positions of the `k`-battery selection a monotonic stack keeps, ascending,
+/// or `None` if the bank has fewer than `k` batteries. A new digit pops the
+/// top of the stack while `evicts(top, digit)` holds and batteries are left to
+/// drop. Equal digits never evict each other, so ties resolve to the leftmost
+/// batteries.
+pub(crate) fn select_indices(
+    bank: &[u32],
+    k: usize,
+    evicts: fn(u32, u32) -> bool,
+) -> Option<Vec<usize>> {
+    if bank.len() < k {
+        return None;
+    }
+    let mut to_remove = bank.len() - k;
+    let mut stack: Vec<usize> = Vec::with_capacity(bank.len());
+    for (i, &digit) in bank.iter().enumerate() {
+        while to_remove > 0 && stack.last().is_some_and(|&top| evicts(bank[top], digit)) {
+            stack.pop();
+            to_remove -= 1;
+        }
+        stack.push(i);
+    }
+    // Whatever is left to drop comes off the end, where it costs least.
+    stack.truncate(k);
+    Some(stack)
+}
+
+/// Whether every digit of `bank` is below `radix`.
+pub(crate) fn in_radix(bank: &[u32], radix: u32) -> bool {
+    bank.iter().all(|&digit| digit < radix)
+}
+
+/// The best selection of exactly `k` batteries: which ones and the joltage.
+/// Returns `None` when the bank has fewer than `k` batteries or a digit that
+/// is not decimal.
+pub fn select(bank: &[u32], k: usize) -> Option<Selection> {
+    select_radix(bank, k, 10)
+}
+
+/// Like [`select`] for a bank whose digits are in `radix` (2 to 36). Digits
+/// compare by value whatever the radix, so the same stack applies; only the
+/// joltage's digits are written in `radix`. Returns `None` for a digit that is
+/// not below `radix`.
+pub fn select_radix(bank: &[u32], k: usize, radix: u32) -> Option<Selection> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    if !in_radix(bank, radix) {
+        return None;
+    }
+    select_indices(bank, k, |top, digit| top < digit)
+        .map(|indices| Selection::from_indices_radix(bank, indices, radix))
+}
+
+/// The selection of exactly `k` batteries with the smallest joltage. A
+/// leading zero makes a joltage smaller, so zeros are picked early. Returns
+/// `None` like [`select`].
+pub fn select_min(bank: &[u32], k: usize) -> Option<Selection> {
+    select_min_radix(bank, k, 10)
+}
+
+/// Like [`select_min`] for a bank whose digits are in `radix` (2 to 36).
+pub fn select_min_radix(bank: &[u32], k: usize, radix: u32) -> Option<Selection> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    if !in_radix(bank, radix) {
+        return None;
+    }
+    select_indices(bank, k, |top, digit| top > digit)
+        .map(|indices| Selection::from_indices_radix(bank, indices, radix))
+}
+
+/// Counts of distinct `k`-battery joltages, used to rank them.
+struct DistinctCounts {
+    /// `next[i][d]` is the first position at or after `i` holding digit `d`.
+    next: Vec<Vec<Option<usize>>>,
+    /// `counts[i][l]` is the number of distinct `l`-digit joltages using only
+    /// batteries at or after `i`, saturating at `u64::MAX`.
+    counts: Vec<Vec<u64>>,
+}
+
+impl DistinctCounts {
+    fn new(bank: &[u32], k: usize) -> Self {
+        let n = bank.len();
+        let next = next_occurrences(bank);
+        // Each distinct joltage is counted once, at the first occurrence of
+        // its leading digit.
+        let mut counts = vec![vec![0_u64; k + 1]; n + 1];
+        for i in (0..=n).rev() {
+            counts[i][0] = 1;
+            for l in 1..=k {
+                counts[i][l] = next[i]
+                    .iter()
+                    .flatten()
+                    .fold(0_u64, |acc, &p| acc.saturating_add(counts[p + 1][l - 1]));
+            }
+        }
+        DistinctCounts { next, counts }
+    }
+}
+
+/// Number of distinct joltages from turning on exactly `k` batteries,
+/// saturating at `u64::MAX`.
+pub fn distinct_joltages(bank: &[u32], k: usize) -> u64 {
+    DistinctCounts::new(bank, k).counts[0][k]
+}
+
+/// The `rank`-th largest distinct joltage from exactly `k` batteries, where
+/// rank 1 is the maximum. Returns `None` when there are fewer than `rank`
+/// distinct joltages or the bank has a digit that is not decimal. The indices
+/// are the leftmost batteries that give it.
+pub fn kth_best(bank: &[u32], k: usize, rank: u64) -> Option<Selection> {
+    kth_best_radix(bank, k, rank, 10)
+}
+
+/// Like [`kth_best`] for a bank whose digits are in `radix` (2 to 36).
+pub fn kth_best_radix(bank: &[u32], k: usize, rank: u64, radix: u32) -> Option<Selection> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    if !in_radix(bank, radix) {
+        return None;
+    }
+    let table = DistinctCounts::new(bank, k);
+    if rank == 0 || table.counts[0][k] < rank {
+        return None;
+    }
+    let mut rank = rank;
+    let mut pos = 0;
+    let mut indices = Vec::with_capacity(k);
+    for left in (1..=k).rev() {
+        // Skip whole groups of joltages that start with a larger digit.
+        let p = table.next[pos]
+            .iter()
+            .rev()
+            .flatten()
+            .copied()
+            .find(|&p| {
+                let group = table.counts[p + 1][left - 1];
+                if rank <= group {
+                    true
+                } else {
+                    rank -= group;
+                    false
+                }
+            })
+            .expect("rank within the distinct count");
+        indices.push(p);
+        pos = p + 1;
+    }
+    Some(Selection::from_indices_radix(bank, indices, radix))
+}
+
+/// Largest joltage from turning on exactly `k` batteries of `bank`.
+///
+/// Returns `None` when the bank has fewer than `k` batteries, or when the
+/// joltage is larger than `u64::MAX`; choosing no batteries gives `Some(0)`,
+/// and a digit that is not decimal gives `None`. Use [`select`] for the exact
+/// digits of any selection.
+pub fn max_joltage(bank: &[u32], k: usize) -> Option<u64> {
+    if !in_radix(bank, 10) {
+        return None;
+    }
+    select_indices(bank, k, |top, digit| top < digit)?
+        .iter()
+        .try_fold(0_u64, |acc, &i| {
+            acc.checked_mul(10)?.checked_add(bank[i] as u64)
+        })
+}
+
+/// Exact total joltage over all banks in `input`, one per line, turning on
+/// `k` batteries in each. Banks with fewer than `k` batteries add nothing.
+pub fn total_joltage(input: &str, k: usize) -> DecimalSum {
+    let mut total = DecimalSum::default();
+    for line in input.lines() {
+        if let Some(selection) = select(&parse_bank(line), k) {
+            total
+                .add(&selection.digits)
+                .expect("a decimal selection has decimal digits");
+        }
+    }
+    total
+}
+
+/// Like [`total_joltage`] for banks in `radix`, with the total in decimal.
+pub fn total_joltage_radix(input: &str, k: usize, radix: u32) -> DecimalSum {
+    let mut total = DecimalSum::default();
+    for line in input.lines() {
+        if let Some(selection) = select_radix(&parse_bank_radix(line, radix), k, radix) {
+            total.add_sum(&selection.decimal_joltage());
+        }
+    }
+    total
+}
+
+/// Best selections for every battery count of one bank.
+///
+/// Dropping a battery at a time, where each step removes the first battery
+/// smaller than the one after it (or the last battery if there is none),
+/// leaves the best selection of every size. So the optimal selections nest,
+/// and one stack pass over the bank yields the whole removal order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Curve {
+    bank: Vec<u32>,
+    /// Battery `i` is on in the best selection of `k` batteries when
+    /// `k >= min_count[i]`.
+    min_count: Vec<usize>,
+}
+
+impl Curve {
+    /// The curve of a decimal bank; its selections panic on a digit above 9.
+    pub fn new(bank: &[u32]) -> Self {
+        let n = bank.len();
+        let mut removed = 0;
+        let mut min_count = vec![0; n];
+        let mut stack: Vec<usize> = Vec::with_capacity(n);
+        for (i, &digit) in bank.iter().enumerate() {
+            while let Some(&top) = stack.last().filter(|&&top| bank[top] < digit) {
+                stack.pop();
+                // Removed as step `removed + 1`, so it stays for larger counts.
+                min_count[top] = n - removed;
+                removed += 1;
+            }
+            stack.push(i);
+        }
+        // The stack is non-increasing; the rest go from its end.
+        for &i in stack.iter().rev() {
+            min_count[i] = n - removed;
+            removed += 1;
+        }
+        Curve {
+            bank: bank.to_vec(),
+            min_count,
+        }
+    }
+
+    /// Number of batteries in the bank, the largest `k` on the curve.
+    pub fn len(&self) -> usize {
+        self.bank.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bank.is_empty()
+    }
+
+    /// The best selection of `k` batteries, or `None` if `k` exceeds the bank.
+    pub fn selection(&self, k: usize) -> Option<Selection> {
+        if k > self.bank.len() {
+            return None;
+        }
+        let indices = (0..self.bank.len())
+            .filter(|&i| self.min_count[i] <= k)
+            .collect();
+        Some(Selection::from_indices(&self.bank, indices))
+    }
+
+    /// One `k  joltage` row for every `k` from 1 to the bank length.
+    pub fn table(&self) -> String {
+        let width = self.bank.len().to_string().len();
+        (1..=self.bank.len())
+            .map(|k| {
+                let selection = self.selection(k).expect("k within the bank");
+                format!("{:>width$}  {}\n", k, selection.digits)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{digits_at, subsets, xorshift};
+
+    /// Best joltage over every `k`-subset of positions.
+    fn brute(bank: &[u32], k: usize) -> Option<u64> {
+        subsets(bank.len(), k)
+            .map(|idx| idx.iter().fold(0, |acc, &i| acc * 10 + bank[i] as u64))
+            .max()
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        // A small deterministic generator keeps the cases reproducible.
+        for state in xorshift(0x2545_f491).take(300) {
+            let n = (state % 12) as usize;
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (4 * i)) % 10) as u32).collect();
+            for k in 0..=n + 1 {
+                assert_eq!(max_joltage(&bank, k), brute(&bank, k), "{:?} k={}", bank, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_short_banks() {
+        assert_eq!(max_joltage(&[5], 2), None);
+        assert_eq!(max_joltage(&[], 1), None);
+        assert_eq!(max_joltage(&[], 0), Some(0));
+        assert_eq!(max_joltage(&[4, 2], 2), Some(42));
+    }
+
+    #[test]
+    fn test_puzzle_banks() {
+        let bank = parse_bank("818181911112111");
+        assert_eq!(max_joltage(&bank, 2), Some(92));
+        assert_eq!(max_joltage(&bank, 12), Some(888911112111));
+        assert_eq!(max_joltage(&bank, 15), Some(818181911112111));
+        // Past 19 batteries the joltage fits only with enough leading zeros.
+        let long = parse_bank(&format!("{}{}", "0".repeat(20), "9".repeat(10)));
+        assert_eq!(max_joltage(&long, 25), Some(9_999_999_999));
+        assert_eq!(max_joltage(&long, 30), Some(9_999_999_999));
+        let nines = parse_bank(&"9".repeat(40));
+        assert_eq!(max_joltage(&nines, 19), Some(9_999_999_999_999_999_999));
+        assert_eq!(max_joltage(&nines, 20), None);
+        assert_eq!(max_joltage(&nines, 41), None);
+    }
+
+    #[test]
+    fn test_select_indices() {
+        let bank = parse_bank("818181911112111");
+        let selection = select(&bank, 2).unwrap();
+        assert_eq!(selection.indices, vec![6, 11]);
+        assert_eq!(selection.digits, "92");
+        assert_eq!(selection.joltage(), Some(92));
+        // Ties keep the leftmost battery.
+        assert_eq!(select(&[9, 9, 9], 2).unwrap().indices, vec![0, 1]);
+        for k in 0..=bank.len() {
+            let selection = select(&bank, k).unwrap();
+            assert_eq!(selection.indices.len(), k);
+            assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+            let from_indices = selection
+                .indices
+                .iter()
+                .fold(0, |acc, &i| acc * 10 + bank[i] as u64);
+            assert_eq!(Some(from_indices), selection.joltage());
+        }
+    }
+
+    #[test]
+    fn test_beyond_u64() {
+        let bank = parse_bank(&"9".repeat(30));
+        let selection = select(&bank, 25).unwrap();
+        assert_eq!(selection.digits, "9".repeat(25));
+        assert_eq!(selection.joltage(), None);
+        assert_eq!(
+            select(&bank, 19).unwrap().joltage(),
+            Some(9_999_999_999_999_999_999)
+        );
+        // 20 nines: 10^20 - 1 overflows a u64.
+        assert_eq!(select(&bank, 20).unwrap().joltage(), None);
+    }
+
+    #[test]
+    fn test_total_joltage() {
+        let input = "987654321111111\n811111111111119\n234234234234278\n818181911112111\n5";
+        assert_eq!(total_joltage(input, 12).to_string(), "3121910778619");
+        assert_eq!(total_joltage(input, 2).to_string(), "357");
+        // Four 15-digit banks, all batteries on.
+        let all = total_joltage(input, 15);
+        assert_eq!(
+            all.to_u128(),
+            Some(987654321111111 + 811111111111119 + 234234234234278 + 818181911112111)
+        );
+        let wide = format!("{}\n{}", "9".repeat(40), "9".repeat(40));
+        assert_eq!(
+            total_joltage(&wide, 40).to_string(),
+            format!("1{}8", "9".repeat(39))
+        );
+    }
+
+    #[test]
+    fn test_curve_matches_select() {
+        for state in xorshift(0x9e37_79b9).take(300) {
+            let n = (state % 16) as usize;
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (4 * i)) % 10) as u32).collect();
+            let curve = Curve::new(&bank);
+            for k in 0..=n {
+                assert_eq!(curve.selection(k), select(&bank, k), "{:?} k={}", bank, k);
+            }
+            assert_eq!(curve.selection(n + 1), None);
+        }
+    }
+
+    #[test]
+    fn test_curve_table() {
+        let curve = Curve::new(&parse_bank("8192"));
+        assert_eq!(curve.table(), "1  9\n2  92\n3  892\n4  8192\n");
+        assert_eq!(Curve::new(&[]).table(), "");
+    }
+
+    /// Every distinct `k`-battery joltage, largest first.
+    fn brute_distinct(bank: &[u32], k: usize) -> Vec<String> {
+        let mut all: Vec<String> = subsets(bank.len(), k)
+            .map(|idx| digits_at(bank, &idx))
+            .collect();
+        all.sort_unstable_by(|a, b| b.cmp(a));
+        all.dedup();
+        all
+    }
+
+    #[test]
+    fn test_min_and_ranks_match_brute_force() {
+        for state in xorshift(0x1234_5678).take(200) {
+            let n = (state % 11) as usize;
+            // Few distinct digits so that repeated joltages are common.
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (3 * i)) % 4) as u32).collect();
+            for k in 0..=n {
+                let all = brute_distinct(&bank, k);
+                assert_eq!(distinct_joltages(&bank, k), all.len() as u64);
+                assert_eq!(
+                    select_min(&bank, k).map(|s| s.digits),
+                    all.last().cloned(),
+                    "{:?} k={}",
+                    bank,
+                    k
+                );
+                for (r, expected) in all.iter().enumerate() {
+                    let selection = kth_best(&bank, k, r as u64 + 1).unwrap();
+                    assert_eq!(
+                        &selection.digits,
+                        expected,
+                        "{:?} k={} r={}",
+                        bank,
+                        k,
+                        r + 1
+                    );
+                    let from_indices: String = selection
+                        .indices
+                        .iter()
+                        .map(|&i| char::from_digit(bank[i], 10).unwrap())
+                        .collect();
+                    assert_eq!(&from_indices, expected);
+                }
+                assert_eq!(kth_best(&bank, k, all.len() as u64 + 1), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_and_ranks() {
+        let bank = parse_bank("818181911112111");
+        assert_eq!(select_min(&bank, 2).unwrap().digits, "11");
+        assert_eq!(select_min(&parse_bank("5031"), 2).unwrap().digits, "01");
+        assert_eq!(kth_best(&bank, 2, 1).unwrap().digits, "92");
+        assert_eq!(kth_best(&bank, 2, 2).unwrap().digits, "91");
+        assert_eq!(kth_best(&bank, 2, 0), None);
+        assert_eq!(select_min(&bank, 16), None);
+        // Counts saturate rather than overflow on long banks.
+        let long = parse_bank(&"0123456789".repeat(30));
+        assert_eq!(distinct_joltages(&long, 40), u64::MAX);
+    }
+
+    #[test]
+    fn test_hex_banks() {
+        let bank = parse_bank_radix("1f9A0e", 16);
+        assert_eq!(bank, vec![1, 15, 9, 10, 0, 14]);
+        let selection = select_radix(&bank, 3, 16).unwrap();
+        assert_eq!(selection.digits, "fae");
+        assert_eq!(selection.indices, vec![1, 3, 5]);
+        assert_eq!(selection.decimal_joltage().to_string(), 0xfae.to_string());
+        // `joltage` keeps reading decimal digits only.
+        assert_eq!(selection.joltage(), None);
+        assert_eq!(select(&parse_bank("1234"), 2).unwrap().joltage(), Some(34));
+
+        let wide = parse_bank_radix(&"f".repeat(17), 16);
+        let all = select_radix(&wide, 17, 16).unwrap();
+        assert_eq!(all.decimal_joltage().to_u128(), Some((1_u128 << 68) - 1));
+
+        // A digit outside the radix gives no selection rather than a panic.
+        assert_eq!(select(&[12], 1), None);
+        assert_eq!(select_radix(&[1, 16], 1, 16), None);
+        assert_eq!(select_min(&[12], 1), None);
+        assert_eq!(kth_best(&[12], 1, 1), None);
+        assert_eq!(max_joltage(&[12], 1), None);
+
+        // Minimum and ranked selections handle digits past 9 as well.
+        assert_eq!(select_min_radix(&bank, 3, 16).unwrap().digits, "10e");
+        assert_eq!(kth_best_radix(&bank, 3, 1, 16).unwrap().digits, "fae");
+        assert_eq!(kth_best_radix(&bank, 3, 2, 16).unwrap().digits, "fa0");
+        assert_eq!(distinct_joltages(&bank, 6), 1);
+        assert_eq!(distinct_joltages(&bank, 1), 6);
+
+        assert_eq!(
+            total_joltage_radix("1f9A0e\nff\n", 2, 16).to_u128(),
+            Some(0xfe + 0xff)
+        );
+        // Radix 10 agrees with the decimal path.
+        let input = "987654321111111\n811111111111119";
+        assert_eq!(total_joltage_radix(input, 12, 10), total_joltage(input, 12));
+    }
+}
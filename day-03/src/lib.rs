@@ -0,0 +1,11 @@
+pub mod budget;
+pub mod constrained;
+pub mod joltage;
+pub mod optimal;
+pub mod parse;
+pub mod ring;
+pub mod stream;
+#[cfg(test)]
+mod testing;
+pub mod total;
+pub mod view;
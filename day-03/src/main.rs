@@ -1,102 +1,227 @@
+use day_03::{
+    budget::{Count, parse_costed, select_within_budget},
+    constrained::{Constraints, select_constrained},
+    joltage::{
+        Curve, Selection, kth_best, max_joltage, parse_bank, parse_bank_radix, select, select_min,
+        select_radix, total_joltage,
+    },
+    optimal::count_optimal,
+    parse::{Mode, parse_banks},
+    ring::select_ring,
+    stream::{total_parallel, total_streaming},
+    total::DecimalSum,
+    view,
+};
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
     let input = include_str!("../input.txt");
-    let part1 = solve_part1(input);
-    println!("Part 1: {}", part1);
-    let part2 = solve_part2(input);
-    println!("Part 2: {}", part2);
-}
-
-fn solve_part1(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|line| {
-            let line = line.trim();
-            if line.is_empty() {
-                return 0;
+    match args.as_slice() {
+        // day-03 --show 12
+        [flag, k] if flag == "--show" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            show(input, k);
+        }
+        // day-03 --curve
+        [flag] if flag == "--curve" => {
+            for (i, line) in input.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+                println!("Bank {}", i + 1);
+                print!("{}", Curve::new(&parse_bank(line)).table());
             }
-
-            // Find the maximum two-digit number we can form from any two batteries
-            let digits: Vec<u32> = line
-                .chars()
-                .filter_map(|c| c.to_digit(10))
-                .collect();
-
-            if digits.len() < 2 {
-                return 0;
+        }
+        // day-03 --batteries 40
+        [flag, k] if flag == "--batteries" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            println!("Total joltage: {}", total_joltage(input, k));
+        }
+        // day-03 --min 12
+        [flag, k] if flag == "--min" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            let total = total_by(input, |bank| select_min(bank, k));
+            println!("Total minimum joltage: {}", total);
+        }
+        // day-03 --rank 12 3
+        [flag, k, rank] if flag == "--rank" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            let rank: u64 = rank.parse().expect("Invalid rank");
+            let total = total_by(input, |bank| kth_best(bank, k, rank));
+            println!("Total joltage at rank {}: {}", rank, total);
+        }
+        // day-03 --gap 12 2 [forbidden positions, e.g. 0,5,9]
+        [flag, k, gap, rest @ ..] if flag == "--gap" && rest.len() <= 1 => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            let constraints = Constraints {
+                min_gap: gap.parse().expect("Invalid gap"),
+                forbidden: rest
+                    .iter()
+                    .flat_map(|list| list.split(','))
+                    .map(|p| p.trim().parse().expect("Invalid position"))
+                    .collect(),
+            };
+            let mut total = DecimalSum::default();
+            for (i, line) in input.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+                match select_constrained(&parse_bank(line), k, &constraints) {
+                    Ok(selection) => total
+                        .add(&selection.digits)
+                        .expect("a decimal selection has decimal digits"),
+                    Err(e) => println!("Bank {}: {}", i + 1, e),
+                }
             }
-
-            let mut max_joltage = 0;
-            // Try all pairs of positions (i, j) where i < j
-            for i in 0..digits.len() {
-                for j in (i + 1)..digits.len() {
-                    let joltage = digits[i] * 10 + digits[j];
-                    max_joltage = max_joltage.max(joltage);
+            println!("Total constrained joltage: {}", total);
+        }
+        // day-03 --optimal 12
+        [flag, k] if flag == "--optimal" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            for (i, line) in input.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+                match count_optimal(&parse_bank(line), k) {
+                    Some(count) => println!("Bank {}: {} optimal selections", i + 1, count),
+                    None => println!("Bank {}: fewer than {} batteries", i + 1, k),
                 }
             }
+        }
+        // day-03 --strict 1-9
+        [flag, alphabet] if flag == "--strict" => {
+            let mode = Mode::Strict(alphabet.parse().unwrap_or_else(|e| panic!("{}", e)));
+            // Part 2 needs the longer banks, so check against its 12.
+            match parse_banks(input, mode, 12) {
+                Ok(banks) => {
+                    let part1: u32 = banks
+                        .iter()
+                        .map(|bank| max_joltage(bank, 2).expect("checked length") as u32)
+                        .sum();
+                    let part2: u64 = banks
+                        .iter()
+                        .map(|bank| max_joltage(bank, 12).expect("checked length"))
+                        .sum();
+                    println!("Part 1: {}", part1);
+                    println!("Part 2: {}", part2);
+                }
+                Err(errors) => {
+                    for e in &errors {
+                        eprintln!("{}", e);
+                    }
+                    std::process::exit(1);
+                }
+            }
+        }
+        // day-03 --ring 12
+        [flag, k] if flag == "--ring" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            let total = total_by(input, |bank| {
+                select_ring(bank, k).map(|ring| ring.selection)
+            });
+            println!("Total ring joltage: {}", total);
+        }
+        // day-03 --parallel
+        [flag] if flag == "--parallel" => {
+            println!("Part 1: {}", total_parallel(input.as_bytes(), 2));
+            println!("Part 2: {}", total_parallel(input.as_bytes(), 12));
+        }
+        // day-03 --stream banks.txt 12
+        [flag, path, k] if flag == "--stream" => {
+            let k: usize = k.parse().expect("Invalid battery count");
+            let file = std::fs::File::open(path).expect("Cannot read input");
+            let total =
+                total_streaming(std::io::BufReader::new(file), k).expect("Cannot read input");
+            println!("Total joltage: {}", total);
+        }
+        // day-03 --budget costed.txt 12 500, or --budget costed.txt <=12 500
+        [flag, path, k, budget] if flag == "--budget" => {
+            let count = match k.strip_prefix("<=") {
+                Some(k) => Count::AtMost(k.parse().expect("Invalid battery count")),
+                None => Count::Exactly(k.parse().expect("Invalid battery count")),
+            };
+            let budget: u64 = budget.parse().expect("Invalid budget");
+            let input = std::fs::read_to_string(path).expect("Cannot read input");
+            let banks = parse_costed(&input).unwrap_or_else(|e| panic!("{}", e));
+            let mut total = DecimalSum::default();
+            for (i, costed) in banks.iter().enumerate() {
+                match select_within_budget(&costed.bank, &costed.costs, count, budget) {
+                    Some(selection) => total
+                        .add(&selection.digits)
+                        .expect("a decimal selection has decimal digits"),
+                    None => println!("Bank {}: no selection within budget", i + 1),
+                }
+            }
+            println!("Total joltage within budget: {}", total);
+        }
+        // day-03 --radix 16 hex-banks.txt 12
+        [flag, radix, path, k] if flag == "--radix" => {
+            let radix: u32 = radix
+                .parse()
+                .ok()
+                .filter(|r| (2..=36).contains(r))
+                .expect("Radix must be between 2 and 36");
+            let k: usize = k.parse().expect("Invalid battery count");
+            let input = std::fs::read_to_string(path).expect("Cannot read input");
+            let mut total = DecimalSum::default();
+            for line in input.lines().filter(|line| !line.trim().is_empty()) {
+                let bank = parse_bank_radix(line, radix);
+                match select_radix(&bank, k, radix) {
+                    Some(selection) => {
+                        let decimal = selection.decimal_joltage();
+                        println!(
+                            "{}  {} (decimal {})",
+                            view::highlight(&bank, &selection),
+                            selection.digits,
+                            decimal
+                        );
+                        total.add_sum(&decimal);
+                    }
+                    None => println!("{}  (fewer than {} batteries)", line.trim(), k),
+                }
+            }
+            println!("Total joltage: {}", total);
+        }
+        _ => {
+            let part1 = solve_part1(input);
+            println!("Part 1: {}", part1);
+            let part2 = solve_part2(input);
+            println!("Part 2: {}", part2);
+        }
+    }
+}
 
-            max_joltage
-        })
+/// Prints every bank with the batteries chosen for `k` highlighted.
+fn show(input: &str, k: usize) {
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        let bank = parse_bank(line);
+        match select(&bank, k) {
+            Some(selection) => println!(
+                "{}  {}",
+                view::highlight(&bank, &selection),
+                selection.digits
+            ),
+            None => println!("{}  (fewer than {} batteries)", line.trim(), k),
+        }
+    }
+}
+
+/// Sums the joltage `choose` picks for every bank that has a selection.
+fn total_by(input: &str, choose: impl Fn(&[u32]) -> Option<Selection>) -> DecimalSum {
+    let mut total = DecimalSum::default();
+    for line in input.lines() {
+        if let Some(selection) = choose(&parse_bank(line)) {
+            total
+                .add(&selection.digits)
+                .expect("a decimal selection has decimal digits");
+        }
+    }
+    total
+}
+
+fn solve_part1(input: &str) -> u32 {
+    // Banks too short for the selection contribute nothing.
+    input
+        .lines()
+        .map(|line| max_joltage(&parse_bank(line), 2).unwrap_or(0) as u32)
         .sum()
 }
 
 fn solve_part2(input: &str) -> u64 {
     input
         .lines()
-        .map(|line| {
-            let line = line.trim();
-            if line.is_empty() {
-                return 0;
-            }
-
-            // Extract digits
-            let digits: Vec<u32> = line
-                .chars()
-                .filter_map(|c| c.to_digit(10))
-                .collect();
-
-            // Need exactly 12 digits
-            if digits.len() < 12 {
-                return 0;
-            }
-
-            // If exactly 12 digits, return the number directly
-            if digits.len() == 12 {
-                return digits.iter().fold(0u64, |acc, &d| acc * 10 + d as u64);
-            }
-
-            // Use greedy stack-based approach to select exactly 12 digits
-            // We need to remove (digits.len() - 12) digits
-            let to_remove = digits.len() - 12;
-            let mut stack: Vec<u32> = Vec::new();
-            let mut removed = 0;
-
-            for (i, &digit) in digits.iter().enumerate() {
-                // Calculate how many digits remain after current position
-                let remaining = digits.len() - i - 1;
-                
-                // While we can remove digits: have items in stack, haven't removed enough, 
-                // and current digit is larger than top of stack
-                while !stack.is_empty()
-                    && removed < to_remove
-                    && digit > *stack.last().unwrap()
-                    && stack.len() + remaining >= 12
-                {
-                    stack.pop();
-                    removed += 1;
-                }
-                
-                // Push current digit
-                stack.push(digit);
-            }
-
-            // If we still have more than 12 digits, remove smallest from end
-            while stack.len() > 12 {
-                stack.pop();
-            }
-
-            // Convert to number
-            stack.iter().fold(0u64, |acc, &d| acc * 10 + d as u64)
-        })
+        .map(|line| max_joltage(&parse_bank(line), 12).unwrap_or(0))
         .sum()
 }
 
@@ -277,9 +402,8 @@ mod tests {
     #[test]
     fn test_single_bank_1234567890() {
         let input = "1234567890";
-        // Note: 0 is not a valid joltage (1-9), but if it appears, we should handle it
-        // Actually, wait - the problem says joltage is 1-9, so maybe 0 shouldn't appear?
-        // But let's test it anyway to be safe
+        // The puzzle's joltages are 1-9. Lenient parsing still takes a 0 as a
+        // battery; `--strict 1-9` rejects it instead.
         // Pairs with 0: 10, 20, 30, 40, 50, 60, 70, 80, 90
         // Pairs without 0: 12, 13, ..., 89
         // Max is 90
@@ -0,0 +1,193 @@
+//! Every battery selection that reaches the best joltage.
+//!
+//! The best joltage for `k` batteries is a fixed digit string, so the optimal
+//! selections are exactly the ways to embed that string in the bank as a
+//! subsequence. Counting them is the classic subsequence-count DP; listing
+//! them walks the embeddings in lexicographic order of their indices.
+
+use crate::{joltage::select_indices, total::DecimalSum};
+
+/// `next[i][d]`: first position at or after `i` holding digit `d`. Each row
+/// covers the digits up to the largest in the bank, so banks in any radix fit.
+pub(crate) fn next_occurrences(bank: &[u32]) -> Vec<Vec<Option<usize>>> {
+    let digits = bank.iter().max().map_or(0, |&top| top as usize + 1);
+    let mut next = vec![vec![None; digits]; bank.len() + 1];
+    for i in (0..bank.len()).rev() {
+        let (row, rest) = next.split_at_mut(i + 1);
+        row[i].copy_from_slice(&rest[0]);
+        row[i][bank[i] as usize] = Some(i);
+    }
+    next
+}
+
+/// Digits of the best `k`-battery selection, compared by value.
+fn best_digits(bank: &[u32], k: usize) -> Option<Vec<u32>> {
+    let indices = select_indices(bank, k, |top, digit| top < digit)?;
+    Some(indices.iter().map(|&i| bank[i]).collect())
+}
+
+/// Number of distinct index sets of `k` batteries that give the best
+/// joltage, or `None` if the bank has fewer than `k` batteries.
+pub fn count_optimal(bank: &[u32], k: usize) -> Option<DecimalSum> {
+    let target = best_digits(bank, k)?;
+    // ways[j]: embeddings of the first j target digits in the bank so far.
+    let mut ways = vec![DecimalSum::default(); k + 1];
+    ways[0].add("1").expect("1 is a decimal number");
+    for &digit in bank {
+        // Walk j downwards so each battery extends an embedding at most once.
+        for j in (1..=k).rev() {
+            if target[j - 1] == digit && !ways[j - 1].is_zero() {
+                let (done, rest) = ways.split_at_mut(j);
+                rest[0].add_sum(&done[j - 1]);
+            }
+        }
+    }
+    ways.pop()
+}
+
+/// Lazily lists the optimal selections, each as ascending battery indices.
+#[derive(Debug, Clone)]
+pub struct OptimalSelections {
+    target: Vec<u32>,
+    next: Vec<Vec<Option<usize>>>,
+    /// `latest[j]`: the rightmost position target digit `j` can take while
+    /// the digits after it still fit.
+    latest: Vec<usize>,
+    current: Option<Vec<usize>>,
+}
+
+impl OptimalSelections {
+    /// Returns `None` if the bank has fewer than `k` batteries.
+    pub fn new(bank: &[u32], k: usize) -> Option<Self> {
+        let target = best_digits(bank, k)?;
+        let mut latest = vec![0; k];
+        let mut end = bank.len();
+        for j in (0..k).rev() {
+            end = (0..end)
+                .rev()
+                .find(|&p| bank[p] == target[j])
+                .expect("the target embeds in the bank");
+            latest[j] = end;
+        }
+        let mut selections = OptimalSelections {
+            target,
+            next: next_occurrences(bank),
+            latest,
+            current: Some(Vec::with_capacity(k)),
+        };
+        selections.fill_from(0);
+        Some(selections)
+    }
+
+    /// Places target digits `from..` at their earliest positions after the
+    /// ones already placed.
+    fn fill_from(&mut self, from: usize) {
+        let current = self.current.as_mut().expect("not exhausted");
+        current.truncate(from);
+        for j in from..self.target.len() {
+            let start = current.last().map_or(0, |&p| p + 1);
+            let p = self.next[start][self.target[j] as usize].expect("room was checked");
+            current.push(p);
+        }
+    }
+}
+
+impl Iterator for OptimalSelections {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let item = self.current.clone()?;
+        // Odometer step: move the rightmost digit that can still move one
+        // match to the right, then pack the digits after it to the left.
+        let step = (0..self.target.len()).rev().find_map(|j| {
+            let p = self.next[item[j] + 1][self.target[j] as usize]?;
+            (p <= self.latest[j]).then_some((j, p))
+        });
+        match step {
+            Some((j, p)) => {
+                let current = self.current.as_mut().expect("not exhausted");
+                current[j] = p;
+                self.fill_from(j + 1);
+            }
+            None => self.current = None,
+        }
+        Some(item)
+    }
+}
+
+impl std::iter::FusedIterator for OptimalSelections {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        joltage::{parse_bank, parse_bank_radix},
+        testing::{digits_at, subsets, xorshift},
+    };
+
+    /// All index sets reaching the best joltage, in lexicographic order.
+    fn brute(bank: &[u32], k: usize) -> Vec<Vec<usize>> {
+        let mut sets: Vec<(String, Vec<usize>)> = subsets(bank.len(), k)
+            .map(|idx| (digits_at(bank, &idx), idx))
+            .collect();
+        let Some(best) = sets.iter().map(|(d, _)| d.clone()).max() else {
+            return Vec::new();
+        };
+        sets.retain(|(d, _)| *d == best);
+        let mut sets: Vec<Vec<usize>> = sets.into_iter().map(|(_, idx)| idx).collect();
+        sets.sort();
+        sets
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for state in xorshift(0x0bad_cafe).take(200) {
+            let n = (state % 12) as usize;
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (3 * i)) % 3) as u32).collect();
+            for k in 0..=n {
+                let expected = brute(&bank, k);
+                let listed: Vec<Vec<usize>> = OptimalSelections::new(&bank, k).unwrap().collect();
+                assert_eq!(listed, expected, "{:?} k={}", bank, k);
+                assert_eq!(
+                    count_optimal(&bank, k).unwrap().to_u128(),
+                    Some(expected.len() as u128)
+                );
+            }
+            assert!(OptimalSelections::new(&bank, n + 1).is_none());
+            assert!(count_optimal(&bank, n + 1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        // Any 12 of 15 equal batteries: C(15, 12).
+        let ones = parse_bank("111111111111111");
+        assert_eq!(count_optimal(&ones, 12).unwrap().to_string(), "455");
+        // 99 needs both nines; 998 can take either 8.
+        let bank = parse_bank("9988776655");
+        assert_eq!(count_optimal(&bank, 2).unwrap().to_string(), "1");
+        let listed: Vec<Vec<usize>> = OptimalSelections::new(&bank, 3).unwrap().collect();
+        assert_eq!(listed, vec![vec![0, 1, 2], vec![0, 1, 3]]);
+        // Digits past 9 from a hex bank fit the tables too.
+        let hex = parse_bank_radix("fafa", 16);
+        assert_eq!(count_optimal(&hex, 2).unwrap().to_string(), "1");
+        let listed: Vec<Vec<usize>> = OptimalSelections::new(&hex, 3).unwrap().collect();
+        assert_eq!(listed, vec![vec![0, 2, 3]]);
+    }
+
+    #[test]
+    fn test_count_beyond_u128() {
+        // C(300, 150) has 89 digits, far beyond a u128.
+        let ones = parse_bank(&"1".repeat(300));
+        let count = count_optimal(&ones, 150).unwrap();
+        assert_eq!(count.to_u128(), None);
+        assert_eq!(count.to_string().len(), 89);
+        // The enumerator is lazy: taking a few does not walk them all.
+        let first: Vec<Vec<usize>> = OptimalSelections::new(&ones, 150)
+            .unwrap()
+            .take(2)
+            .collect();
+        assert_eq!(first[0], (0..150).collect::<Vec<_>>());
+        assert_eq!(first[1], (0..149).chain([150]).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,254 @@
+//! Reading banks from input, strictly or leniently.
+//!
+//! Lenient parsing is what the puzzle solvers have always done: anything that
+//! is not a decimal digit is dropped and short banks are left for the
+//! selection to skip. Strict parsing rejects stray characters, digits outside
+//! the allowed alphabet and banks too short for the selection, and says where.
+
+use std::{fmt, str::FromStr};
+
+use crate::joltage::parse_bank;
+
+/// The battery digits a bank may hold. Alphabets are decimal: strict parsing
+/// reports a hex digit such as `a` as [`BankError::NotADigit`], so banks in
+/// other radixes go through
+/// [`parse_bank_radix`](crate::joltage::parse_bank_radix) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphabet {
+    allowed: [bool; 10],
+}
+
+impl Alphabet {
+    /// Every digit from 0 to 9.
+    pub const ALL: Alphabet = Alphabet {
+        allowed: [true; 10],
+    };
+
+    /// Whether `digit` is allowed; digits past 9 never are.
+    pub fn contains(&self, digit: u32) -> bool {
+        self.allowed.get(digit as usize).copied().unwrap_or(false)
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = String;
+
+    /// Parses digits and inclusive ranges, e.g. `1-9` or `0,2,4-6`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digit = |t: &str| {
+            t.trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|&d| d < 10)
+                .ok_or_else(|| format!("`{}` is not a digit", t.trim()))
+        };
+        let mut allowed = [false; 10];
+        for part in s.split(',') {
+            let (lo, hi) = match part.split_once('-') {
+                Some((lo, hi)) => (digit(lo)?, digit(hi)?),
+                None => (digit(part)?, digit(part)?),
+            };
+            if lo > hi {
+                return Err(format!("`{}` is an empty range", part.trim()));
+            }
+            allowed[lo as usize..=hi as usize].fill(true);
+        }
+        Ok(Alphabet { allowed })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Drop non-digits and keep short banks, as the solvers always did.
+    Lenient,
+    /// Reject anything outside the alphabet and banks shorter than `k`.
+    Strict(Alphabet),
+}
+
+/// Why a bank was rejected in strict mode. Lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BankError {
+    /// A character that is not a decimal digit.
+    NotADigit {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// A digit outside the alphabet.
+    Disallowed {
+        line: usize,
+        column: usize,
+        digit: u32,
+    },
+    /// Fewer batteries than the selection turns on.
+    TooShort {
+        line: usize,
+        len: usize,
+        needed: usize,
+    },
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BankError::NotADigit {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {}, column {}: `{}` is not a digit",
+                line, column, found
+            ),
+            BankError::Disallowed {
+                line,
+                column,
+                digit,
+            } => write!(
+                f,
+                "line {}, column {}: digit {} is not allowed",
+                line, column, digit
+            ),
+            BankError::TooShort { line, len, needed } => write!(
+                f,
+                "line {}: bank has {} batteries, needs at least {}",
+                line, len, needed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BankError {}
+
+/// Checks one bank line strictly. Surrounding whitespace is allowed, and
+/// columns count from the start of the untrimmed line.
+fn parse_strict(
+    line_text: &str,
+    line: usize,
+    alphabet: &Alphabet,
+    k: usize,
+) -> Result<Vec<u32>, BankError> {
+    let text = line_text.trim_start();
+    let indent = line_text.chars().count() - text.chars().count();
+    let bank = text
+        .trim_end()
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let column = indent + i + 1;
+            let digit = c.to_digit(10).ok_or(BankError::NotADigit {
+                line,
+                column,
+                found: c,
+            })?;
+            if !alphabet.contains(digit) {
+                return Err(BankError::Disallowed {
+                    line,
+                    column,
+                    digit,
+                });
+            }
+            Ok(digit)
+        })
+        .collect::<Result<Vec<u32>, BankError>>()?;
+    if bank.len() < k {
+        return Err(BankError::TooShort {
+            line,
+            len: bank.len(),
+            needed: k,
+        });
+    }
+    Ok(bank)
+}
+
+/// Reads every non-blank line as a bank for a `k`-battery selection. Strict
+/// mode reports the first problem on each bad line, for all lines; lenient
+/// mode never fails.
+pub fn parse_banks(input: &str, mode: Mode, k: usize) -> Result<Vec<Vec<u32>>, Vec<BankError>> {
+    let mut banks = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match mode {
+            Mode::Lenient => banks.push(parse_bank(line)),
+            Mode::Strict(alphabet) => match parse_strict(line, i + 1, &alphabet, k) {
+                Ok(bank) => banks.push(bank),
+                Err(e) => errors.push(e),
+            },
+        }
+    }
+    if errors.is_empty() {
+        Ok(banks)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabet() {
+        let nonzero: Alphabet = "1-9".parse().unwrap();
+        assert!(!nonzero.contains(0) && nonzero.contains(1) && nonzero.contains(9));
+        let evens: Alphabet = "0,2,4-4, 6-8".parse().unwrap();
+        assert!(evens.contains(7) && !evens.contains(5) && !evens.contains(9));
+        assert!("1-x".parse::<Alphabet>().is_err());
+        assert!("10".parse::<Alphabet>().is_err());
+        assert!("5-3".parse::<Alphabet>().is_err());
+        assert!(Alphabet::ALL.contains(0));
+        assert!(!Alphabet::ALL.contains(10) && !Alphabet::ALL.contains(u32::MAX));
+        assert!("0-f".parse::<Alphabet>().is_err());
+    }
+
+    #[test]
+    fn test_lenient_keeps_old_behaviour() {
+        let banks = parse_banks("12a34\n\n5\n", Mode::Lenient, 2).unwrap();
+        assert_eq!(banks, vec![vec![1, 2, 3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn test_strict_reports_every_bad_line() {
+        let nonzero = Mode::Strict("1-9".parse().unwrap());
+        let errors = parse_banks("12a34\n1234\n\n5\n1203\n", nonzero, 2).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                BankError::NotADigit {
+                    line: 1,
+                    column: 3,
+                    found: 'a'
+                },
+                BankError::TooShort {
+                    line: 4,
+                    len: 1,
+                    needed: 2
+                },
+                BankError::Disallowed {
+                    line: 5,
+                    column: 3,
+                    digit: 0
+                },
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "line 1, column 3: `a` is not a digit"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "line 4: bank has 1 batteries, needs at least 2"
+        );
+        assert_eq!(
+            parse_banks(" 1234 \n98\n", nonzero, 2),
+            Ok(vec![vec![1, 2, 3, 4], vec![9, 8]])
+        );
+        assert_eq!(
+            parse_banks("  12 3\n", nonzero, 2).unwrap_err()[0].to_string(),
+            "line 1, column 5: ` ` is not a digit"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Selection from banks wired in a ring, where the chosen batteries may wrap
+//! from the end of the line back to the start.
+//!
+//! Every rotation has all `n` batteries, so the first chosen battery can be
+//! any of them and the best joltage starts with the bank's largest digit.
+//! Only rotations starting at a copy of it are candidates, and fewer still
+//! can win:
+//!
+//! - With `c` the largest digit, rotation `cY` does at least as well as `Yc`:
+//!   a pick from `Yc` that ends in that `c` is `Sc`, and `cS`, which `cY`
+//!   also holds, is never smaller. So in a run of `c`s only the first start
+//!   counts, and start 0 for ties when a run wraps around the end.
+//! - A ring that repeats with period `p` has only `p` distinct rotations.
+//!
+//! The `r` starts left run the greedy in lockstep, one digit at a time, and a
+//! candidate is dropped as soon as its digit falls behind the best. With a
+//! next-occurrence table over the doubled bank each step is a lookup, so the
+//! search is `O(r * k)` against `O(n²)` for one greedy pass per rotation. A
+//! bank of equal digits has `r = 1`; it takes many short runs of the largest
+//! digit that keep tying to approach the quadratic bound.
+
+use crate::{
+    joltage::{Selection, in_radix},
+    optimal::next_occurrences,
+};
+
+/// The best selection from a ring of batteries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingSelection {
+    /// Bank position the rotation starts at, which is also the first chosen
+    /// battery unless nothing is chosen.
+    pub start: usize,
+    /// The selection within the rotated bank, `bank[start..]` then
+    /// `bank[..start]`.
+    pub selection: Selection,
+}
+
+impl RingSelection {
+    /// Positions of the chosen batteries in the original bank, in ring order
+    /// from `start`.
+    pub fn positions(&self, len: usize) -> Vec<usize> {
+        self.selection
+            .indices
+            .iter()
+            .map(|&i| (self.start + i) % len)
+            .collect()
+    }
+}
+
+/// The largest digit, and its first position, that a greedy step can take
+/// after position `after` while `left - 1` batteries still fit before `end`.
+fn step(
+    next: &[Vec<Option<usize>>],
+    after: usize,
+    end: usize,
+    left: usize,
+) -> Option<(u32, usize)> {
+    let last = end.checked_sub(left)?;
+    (0..next[after].len())
+        .rev()
+        .find_map(|d| next[after][d].filter(|&p| p <= last).map(|p| (d as u32, p)))
+}
+
+/// The smallest shift that maps the ring onto itself; `n` for a ring with no
+/// shorter period. Rotations that far apart are the same string.
+fn ring_period(bank: &[u32]) -> usize {
+    let n = bank.len();
+    // failure[i]: length of the longest proper border of bank[..=i].
+    let mut failure = vec![0; n];
+    for i in 1..n {
+        let mut len = failure[i - 1];
+        while len > 0 && bank[i] != bank[len] {
+            len = failure[len - 1];
+        }
+        if bank[i] == bank[len] {
+            len += 1;
+        }
+        failure[i] = len;
+    }
+    let period = n - failure.last().copied().unwrap_or(0);
+    if n.is_multiple_of(period) { period } else { n }
+}
+
+/// The earliest start of a best rotation for `k >= 1` batteries, and how many
+/// candidate steps the search took.
+fn best_start(next: &[Vec<Option<usize>>], bank: &[u32], k: usize, top: u32) -> (usize, usize) {
+    let n = bank.len();
+    // (start, position of the last chosen battery) for every live candidate.
+    // A start past the ring's period repeats an earlier rotation, and one
+    // inside a run of the largest digit does no better than the start before
+    // it, so either can only tie and lose the tie.
+    let mut live: Vec<(usize, usize)> = (0..ring_period(bank))
+        .filter(|&s| bank[s] == top && (s == 0 || bank[s - 1] != top))
+        .map(|s| (s, s))
+        .collect();
+    let mut work = 0;
+    for left in (1..k).rev() {
+        work += live.len();
+        let steps: Vec<(u32, usize)> = live
+            .iter()
+            .map(|&(s, p)| step(next, p + 1, s + n, left).expect("a rotation holds k batteries"))
+            .collect();
+        let best = steps
+            .iter()
+            .map(|&(d, _)| d)
+            .max()
+            .expect("a candidate is live");
+        live = live
+            .iter()
+            .zip(&steps)
+            .filter(|(_, (d, _))| *d == best)
+            .map(|(&(s, _), &(_, p))| (s, p))
+            .collect();
+    }
+    (live[0].0, work)
+}
+
+/// Best `k`-battery selection over all rotations of a ring `bank`, or `None`
+/// if it has fewer than `k` batteries or a digit that is not decimal. Ties go
+/// to the earliest start.
+pub fn select_ring(bank: &[u32], k: usize) -> Option<RingSelection> {
+    select_ring_radix(bank, k, 10)
+}
+
+/// Like [`select_ring`] for a ring whose digits are in `radix` (2 to 36).
+pub fn select_ring_radix(bank: &[u32], k: usize, radix: u32) -> Option<RingSelection> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36");
+    let n = bank.len();
+    if k > n || !in_radix(bank, radix) {
+        return None;
+    }
+    let Some(&top) = bank.iter().max().filter(|_| k > 0) else {
+        return Some(RingSelection {
+            start: 0,
+            selection: Selection::from_indices_radix(bank, Vec::new(), radix),
+        });
+    };
+    // Rotation `s` is doubled[s..s + n].
+    let doubled: Vec<u32> = bank.iter().chain(&bank[..n - 1]).copied().collect();
+    let next = next_occurrences(&doubled);
+
+    let (start, _) = best_start(&next, bank, k, top);
+
+    // Replay the winning rotation to recover its indices.
+    let mut indices = vec![0];
+    for left in (1..k).rev() {
+        let after = start + indices.last().expect("first battery chosen") + 1;
+        let (_, p) = step(&next, after, start + n, left).expect("replaying a live candidate");
+        indices.push(p - start);
+    }
+    let rotated: Vec<u32> = doubled[start..start + n].to_vec();
+    Some(RingSelection {
+        start,
+        selection: Selection::from_indices_radix(&rotated, indices, radix),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        joltage::{parse_bank, parse_bank_radix, select},
+        testing::xorshift,
+    };
+
+    /// Best digits over one greedy pass per rotation, and the earliest first
+    /// battery that gives them.
+    fn brute(bank: &[u32], k: usize) -> Option<(String, usize)> {
+        (0..bank.len().max(1))
+            .filter_map(|r| {
+                let rotated: Vec<u32> = bank[r..].iter().chain(&bank[..r]).copied().collect();
+                let s = select(&rotated, k)?;
+                let first = s.indices.first().map_or(0, |&i| (r + i) % bank.len());
+                Some((s.digits, first))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for state in xorshift(0x21e6).take(2000) {
+            let n = (state % 13) as usize;
+            let digits = 2 + (state >> 60) % 8;
+            // Some banks repeat a short block, to exercise the period.
+            let period = match state >> 58 {
+                0..8 => 1 + (state >> 52) as usize % 4,
+                _ => n,
+            };
+            let bank: Vec<u32> = (0..n)
+                .map(|i| ((state >> (4 * (i % period.max(1)))) % digits) as u32)
+                .collect();
+            for k in 0..=n + 1 {
+                let got = select_ring(&bank, k);
+                assert_eq!(
+                    got.as_ref().map(|r| (r.selection.digits.clone(), r.start)),
+                    brute(&bank, k),
+                    "{:?} k={}",
+                    bank,
+                    k
+                );
+                if let Some(ring) = got {
+                    let positions = ring.positions(n);
+                    let digits: String = positions
+                        .iter()
+                        .map(|&i| char::from_digit(bank[i], 10).unwrap())
+                        .collect();
+                    assert_eq!(digits, ring.selection.digits);
+                    assert!(ring.selection.indices.windows(2).all(|w| w[0] < w[1]));
+                    assert!(ring.selection.indices.last().is_none_or(|&i| i < n));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        // The line alone gives 839; the ring starts at the 9 and wraps.
+        let bank = parse_bank("18239");
+        assert_eq!(select(&bank, 3).unwrap().digits, "839");
+        let ring = select_ring(&bank, 3).unwrap();
+        assert_eq!(ring.start, 4);
+        assert_eq!(ring.selection.digits, "983");
+        assert_eq!(ring.selection.indices, vec![0, 2, 4]);
+        assert_eq!(ring.positions(bank.len()), vec![4, 1, 3]);
+        // Ties go to the earliest start.
+        let ring = select_ring(&parse_bank("9191"), 2).unwrap();
+        assert_eq!((ring.start, ring.selection.digits.as_str()), (0, "99"));
+        // Every rotation ties, so the earliest start wins.
+        let n = 200;
+        let ring = select_ring(&vec![7; n], n - 1).unwrap();
+        assert_eq!(ring.start, 0);
+        assert_eq!(ring.selection.digits, "7".repeat(n - 1));
+        assert_eq!(ring.positions(n), (0..n - 1).collect::<Vec<_>>());
+        // Hex rings select digits past 9 without running off the tables.
+        let ring = select_ring_radix(&parse_bank_radix("1a2F", 16), 3, 16).unwrap();
+        assert_eq!((ring.start, ring.selection.digits.as_str()), (3, "fa2"));
+        assert_eq!(select_ring(&[12], 1), None);
+    }
+
+    #[test]
+    fn test_ring_period() {
+        assert_eq!(ring_period(&[7; 5]), 1);
+        assert_eq!(ring_period(&parse_bank("767676")), 2);
+        assert_eq!(ring_period(&parse_bank("76767")), 5);
+        assert_eq!(ring_period(&parse_bank("1")), 1);
+    }
+
+    #[test]
+    fn test_work_beats_one_pass_per_rotation() {
+        // One greedy pass per rotation costs about n steps each, n² in all.
+        let n = 2000;
+        let k = n - 1;
+        let banks = [
+            vec![7; n],
+            (0..n).map(|i| if i == n / 2 { 6 } else { 7 }).collect(),
+            (0..n).map(|i| [7, 6, 7, 5][i % 4]).collect::<Vec<u32>>(),
+        ];
+        for bank in banks {
+            let doubled: Vec<u32> = bank.iter().chain(&bank[..n - 1]).copied().collect();
+            let (_, work) = best_start(&next_occurrences(&doubled), &bank, k, 7);
+            // At most the two starts of a run that wraps around the end.
+            assert!(work <= 2 * k, "{} steps", work);
+            let ring = select_ring(&bank, k).unwrap();
+            assert_eq!(Some((ring.selection.digits, ring.start)), brute(&bank, k));
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Byte-level, parallel and streaming solvers for very large inputs.
+//!
+//! The monotonic stack only needs to know how many digits are still to come
+//! while fewer than `k` remain. So [`Selector`] holds each digit back until
+//! `k` more have arrived, pops freely before that, and keeps at most `k`
+//! digits on the stack: a bank of any length is processed in `O(k)` memory.
+
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead},
+};
+
+use rayon::prelude::*;
+
+use crate::total::DecimalSum;
+
+/// Best `k`-battery selection of a bank fed one digit at a time.
+#[derive(Debug, Clone)]
+pub struct Selector {
+    k: usize,
+    /// Non-increasing best prefix, never longer than `k`.
+    stack: Vec<u8>,
+    /// The last `k` digits seen, not yet on the stack.
+    pending: VecDeque<u8>,
+}
+
+impl Selector {
+    pub fn new(k: usize) -> Self {
+        Selector {
+            k,
+            stack: Vec::with_capacity(k),
+            pending: VecDeque::with_capacity(k + 1),
+        }
+    }
+
+    /// Pushes `digit` knowing at least `remaining` digits come after it.
+    fn place(&mut self, digit: u8, remaining: usize) {
+        while self.stack.last().is_some_and(|&top| top < digit)
+            && self.stack.len() + remaining >= self.k
+        {
+            self.stack.pop();
+        }
+        // A digit that lands past position `k` can never move up into the
+        // selection: it would be popped before anything below it.
+        if self.stack.len() < self.k {
+            self.stack.push(digit);
+        }
+    }
+
+    /// Feeds the next battery digit, 0 to 9.
+    pub fn push(&mut self, digit: u8) {
+        self.pending.push_back(digit);
+        if self.pending.len() > self.k {
+            let digit = self.pending.pop_front().expect("more than k pending");
+            self.place(digit, self.k);
+        }
+    }
+
+    /// The selected digits, or `None` if fewer than `k` were fed. Resets the
+    /// selector for the next bank.
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        self.finish_with(<[u8]>::to_vec)
+    }
+
+    /// Like [`finish`](Selector::finish), handing the selected digits to `f`
+    /// instead, so the selector keeps its buffers for the next bank.
+    pub fn finish_with<R>(&mut self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        while let Some(digit) = self.pending.pop_front() {
+            let remaining = self.pending.len();
+            self.place(digit, remaining);
+        }
+        let result = (self.stack.len() == self.k).then(|| f(&self.stack));
+        self.stack.clear();
+        result
+    }
+}
+
+/// Largest joltage of one bank given as raw bytes; bytes that are not ASCII
+/// digits are skipped. Like [`max_joltage`](crate::joltage::max_joltage),
+/// returns `None` for a short bank or a joltage that does not fit a `u64`.
+pub fn max_joltage_bytes(bank: &[u8], k: usize) -> Option<u64> {
+    let mut selector = Selector::new(k);
+    for &b in bank.iter().filter(|b| b.is_ascii_digit()) {
+        selector.push(b - b'0');
+    }
+    selector
+        .finish_with(|digits| {
+            digits
+                .iter()
+                .try_fold(0_u64, |acc, &d| acc.checked_mul(10)?.checked_add(d as u64))
+        })
+        .flatten()
+}
+
+/// Adds the bank `selector` has been fed to `total`, if it was long enough.
+fn add_finished(total: &mut DecimalSum, selector: &mut Selector) {
+    selector.finish_with(|digits| total.add_digit_values(digits));
+}
+
+/// Exact total joltage over all banks, one per line, with the lines spread
+/// across threads. Banks with fewer than `k` batteries add nothing. Each
+/// worker keeps one selector and one running total for all its lines.
+pub fn total_parallel(input: &[u8], k: usize) -> DecimalSum {
+    input
+        .par_split(|&b| b == b'\n')
+        .fold(
+            || (Selector::new(k), DecimalSum::default()),
+            |(mut selector, mut total), line| {
+                for &b in line.iter().filter(|b| b.is_ascii_digit()) {
+                    selector.push(b - b'0');
+                }
+                add_finished(&mut total, &mut selector);
+                (selector, total)
+            },
+        )
+        .map(|(_, total)| total)
+        .reduce(DecimalSum::default, |mut a, b| {
+            a.add_sum(&b);
+            a
+        })
+}
+
+/// Exact total joltage over banks read from `reader`, one per line, for any
+/// `k`. Memory stays `O(k)` however long the file or its lines are.
+pub fn total_streaming<R: BufRead>(mut reader: R, k: usize) -> io::Result<DecimalSum> {
+    let mut total = DecimalSum::default();
+    let mut selector = Selector::new(k);
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for &b in buf {
+            if b == b'\n' {
+                add_finished(&mut total, &mut selector);
+            } else if b.is_ascii_digit() {
+                selector.push(b - b'0');
+            }
+        }
+        let len = buf.len();
+        reader.consume(len);
+    }
+    add_finished(&mut total, &mut selector);
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        joltage::{max_joltage, parse_bank, select, total_joltage},
+        testing::xorshift,
+    };
+
+    #[test]
+    fn test_selector_matches_select() {
+        for state in xorshift(0x5eed).take(500) {
+            let n = (state % 16) as usize;
+            let bank: Vec<u32> = (0..n).map(|i| ((state >> (4 * i)) % 10) as u32).collect();
+            for k in 0..=n + 1 {
+                let mut selector = Selector::new(k);
+                for &d in &bank {
+                    selector.push(d as u8);
+                }
+                let expected = select(&bank, k).map(|s| s.digits.into_bytes());
+                let got = selector
+                    .finish()
+                    .map(|d| d.iter().map(|&d| b'0' + d).collect::<Vec<u8>>());
+                assert_eq!(got, expected, "{:?} k={}", bank, k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes_and_parallel() {
+        let input = "987654321111111\n811111111111119\n234234234234278\n818181911112111\n5\n";
+        assert_eq!(total_parallel(input.as_bytes(), 2).to_u128(), Some(357));
+        assert_eq!(
+            total_parallel(input.as_bytes(), 12).to_u128(),
+            Some(3121910778619)
+        );
+        // Joltages too long for any integer still add up exactly.
+        assert_eq!(
+            total_parallel(input.as_bytes(), 15),
+            total_joltage(input, 15)
+        );
+        let long = "9".repeat(60);
+        assert_eq!(
+            total_parallel(long.as_bytes(), 50),
+            total_joltage(&long, 50)
+        );
+        for line in input.lines() {
+            assert_eq!(
+                max_joltage_bytes(line.as_bytes(), 12),
+                max_joltage(&parse_bank(line), 12)
+            );
+        }
+        // Selections past 19 batteries agree with the decimal path too.
+        let long = format!("{}{}\n{}", "0".repeat(20), "7".repeat(10), "9".repeat(25));
+        for line in long.lines() {
+            for k in [19, 20, 25, 30] {
+                assert_eq!(
+                    max_joltage_bytes(line.as_bytes(), k),
+                    max_joltage(&parse_bank(line), k)
+                );
+            }
+        }
+        assert_eq!(max_joltage_bytes("9".repeat(25).as_bytes(), 20), None);
+    }
+
+    #[test]
+    fn test_streaming() {
+        let input = "987654321111111\n811111111111119\n234234234234278\n818181911112111";
+        // A tiny buffer splits banks across reads.
+        let reader = io::BufReader::with_capacity(4, input.as_bytes());
+        let total = total_streaming(reader, 12).unwrap();
+        assert_eq!(total, total_joltage(input, 12));
+        assert_eq!(
+            total_streaming(input.as_bytes(), 30).unwrap().to_string(),
+            "0"
+        );
+
+        // One long bank: the best 50 of 1,000,000 digits.
+        let long: String = (0..1_000_000)
+            .map(|i| (b'0' + (i * 7 % 10) as u8) as char)
+            .collect();
+        let streamed = total_streaming(long.as_bytes(), 50).unwrap();
+        assert_eq!(streamed, total_joltage(&long, 50));
+    }
+}
@@ -0,0 +1,30 @@
+//! Fixtures shared by the unit tests.
+
+/// Pseudo-random states from a xorshift generator, the same on every run so
+/// a failing case can be reproduced.
+pub fn xorshift(seed: u64) -> impl Iterator<Item = u64> {
+    std::iter::successors(Some(seed), |&state| {
+        let mut state = state;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        Some(state)
+    })
+    .skip(1)
+}
+
+/// Every set of `k` of the positions `0..n`, each ascending, for brute-force
+/// checks on small banks.
+pub fn subsets(n: usize, k: usize) -> impl Iterator<Item = Vec<usize>> {
+    (0_u32..1 << n)
+        .filter(move |mask| mask.count_ones() as usize == k)
+        .map(move |mask| (0..n).filter(|i| mask & (1 << i) != 0).collect())
+}
+
+/// The decimal digits of `bank` at `indices`.
+pub fn digits_at(bank: &[u32], indices: &[usize]) -> String {
+    indices
+        .iter()
+        .map(|&i| char::from_digit(bank[i], 10).unwrap())
+        .collect()
+}
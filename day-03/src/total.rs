@@ -0,0 +1,204 @@
+//! Exact sums of joltages too long for a machine integer.
+
+use std::fmt;
+
+/// A non-negative decimal number that only ever gets added to.
+///
+/// Joltages already come out of a selection as decimal digits, so schoolbook
+/// addition on those digits is all a total needs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecimalSum {
+    /// Least-significant digit first, without trailing zeros; zero is empty.
+    digits: Vec<u8>,
+}
+
+/// A number given to [`DecimalSum::add`] had a character that is not a
+/// decimal digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotDecimal {
+    pub found: char,
+}
+
+impl fmt::Display for NotDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a decimal digit", self.found)
+    }
+}
+
+impl std::error::Error for NotDecimal {}
+
+impl DecimalSum {
+    /// Adds a number written as decimal digits, most significant first. The
+    /// sum is unchanged if any character is not a decimal digit.
+    pub fn add(&mut self, number: &str) -> Result<(), NotDecimal> {
+        let added = number
+            .chars()
+            .rev()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as u8)
+                    .ok_or(NotDecimal { found: c })
+            })
+            .collect::<Result<Vec<u8>, NotDecimal>>()?;
+        self.add_digits(&added);
+        Ok(())
+    }
+
+    /// Adds a number given as digit values 0 to 9, most significant first.
+    pub(crate) fn add_digit_values(&mut self, digits: &[u8]) {
+        debug_assert!(digits.iter().all(|&d| d < 10), "decimal digit values");
+        self.add_with(digits.len(), |i| digits[digits.len() - 1 - i]);
+    }
+
+    /// Adds another sum.
+    pub fn add_sum(&mut self, other: &DecimalSum) {
+        self.add_digits(&other.digits);
+    }
+
+    /// Converts digits in `radix` (2 to 36), most significant first, or
+    /// `None` if one is not a digit in that radix.
+    pub fn from_radix(number: &str, radix: u32) -> Option<DecimalSum> {
+        let mut sum = DecimalSum::default();
+        for c in number.chars() {
+            let digit = c.to_digit(radix)?;
+            sum.mul_small(radix);
+            sum.add_digits(&[(digit % 10) as u8, (digit / 10) as u8]);
+        }
+        Some(sum)
+    }
+
+    /// Multiplies in place by a small factor.
+    fn mul_small(&mut self, factor: u32) {
+        let mut carry = 0;
+        for digit in &mut self.digits {
+            let v = *digit as u32 * factor + carry;
+            *digit = (v % 10) as u8;
+            carry = v / 10;
+        }
+        while carry > 0 {
+            self.digits.push((carry % 10) as u8);
+            carry /= 10;
+        }
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// Adds least-significant-first digits.
+    fn add_digits(&mut self, added: &[u8]) {
+        self.add_with(added.len(), |i| added[i]);
+    }
+
+    /// Adds the `len`-digit number whose `i`-th least significant digit is
+    /// `digit(i)`.
+    fn add_with(&mut self, len: usize, digit: impl Fn(usize) -> u8) {
+        if len > self.digits.len() {
+            self.digits.resize(len, 0);
+        }
+        let mut carry = 0;
+        for (i, d) in self.digits.iter_mut().enumerate() {
+            let v = *d + if i < len { digit(i) } else { 0 } + carry;
+            *d = v % 10;
+            carry = v / 10;
+        }
+        if carry > 0 {
+            self.digits.push(carry);
+        }
+        // Leading zeros in the added number must not linger as trailing zeros.
+        while self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+    }
+
+    /// The value as a `u128`, or `None` if it does not fit.
+    pub fn to_u128(&self) -> Option<u128> {
+        self.digits.iter().rev().try_fold(0_u128, |acc, &d| {
+            acc.checked_mul(10)?.checked_add(d as u128)
+        })
+    }
+}
+
+impl fmt::Display for DecimalSum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return f.pad_integral(true, "", "0");
+        }
+        let digits: String = self
+            .digits
+            .iter()
+            .rev()
+            .map(|&d| (b'0' + d) as char)
+            .collect();
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let mut sum = DecimalSum::default();
+        assert_eq!(sum.to_string(), "0");
+        sum.add("999").unwrap();
+        sum.add("1").unwrap();
+        assert_eq!(sum.to_string(), "1000");
+        sum.add("0042").unwrap();
+        sum.add("0").unwrap();
+        sum.add("").unwrap();
+        assert_eq!(sum.to_string(), "1042");
+        assert_eq!(sum.to_u128(), Some(1042));
+        let copy = sum.clone();
+        sum.add_sum(&copy);
+        assert_eq!(sum.to_string(), "2084");
+        assert!(!sum.is_zero() && DecimalSum::default().is_zero());
+
+        // A bad number is rejected whole and leaves the sum alone.
+        assert_eq!(sum.add("12x4"), Err(NotDecimal { found: 'x' }));
+        assert_eq!(
+            sum.add("-1").unwrap_err().to_string(),
+            "`-` is not a decimal digit"
+        );
+        assert_eq!(sum.to_string(), "2084");
+
+        sum.add_digit_values(&[0, 9, 1, 6]);
+        assert_eq!(sum.to_string(), "3000");
+    }
+
+    #[test]
+    fn test_matches_u128() {
+        let values = [0_u128, 7, 99, 12_345, u64::MAX as u128, 10_u128.pow(30) - 1];
+        let mut sum = DecimalSum::default();
+        let mut expected = 0_u128;
+        for v in values.iter().cycle().take(40) {
+            sum.add(&v.to_string()).unwrap();
+            expected += v;
+            assert_eq!(sum.to_u128(), Some(expected));
+        }
+        let mut big = DecimalSum::default();
+        big.add(&u128::MAX.to_string()).unwrap();
+        big.add("1").unwrap();
+        assert_eq!(big.to_u128(), None);
+        assert_eq!(big.to_string(), "340282366920938463463374607431768211456");
+    }
+
+    #[test]
+    fn test_from_radix() {
+        assert_eq!(DecimalSum::from_radix("ff", 16).unwrap().to_string(), "255");
+        assert_eq!(DecimalSum::from_radix("FF", 16).unwrap().to_string(), "255");
+        assert_eq!(DecimalSum::from_radix("1010", 2).unwrap().to_string(), "10");
+        assert_eq!(DecimalSum::from_radix("0", 16).unwrap().to_string(), "0");
+        assert_eq!(DecimalSum::from_radix("12", 2), None);
+        assert_eq!(
+            DecimalSum::from_radix(&"z".repeat(30), 36)
+                .unwrap()
+                .to_string(),
+            "48873677980689257489322752273774603865660850175"
+        );
+    }
+}
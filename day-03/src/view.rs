@@ -0,0 +1,43 @@
+//! Terminal rendering of a bank with its chosen batteries highlighted.
+
+use crate::joltage::Selection;
+
+const CHOSEN: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// The bank's digits with the selected ones in bold green, using ANSI escapes.
+/// Panics if a digit is not below the selection's radix, which a selection
+/// made from the same bank rules out.
+pub fn highlight(bank: &[u32], selection: &Selection) -> String {
+    let mut chosen = selection.indices.iter().peekable();
+    let mut out = String::new();
+    for (i, &digit) in bank.iter().enumerate() {
+        let digit = char::from_digit(digit, selection.radix).expect("battery digit");
+        if chosen.next_if_eq(&&i).is_some() {
+            out.push_str(CHOSEN);
+            out.push(digit);
+            out.push_str(RESET);
+        } else {
+            out.push(digit);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::joltage::select;
+
+    #[test]
+    fn test_highlight() {
+        let bank = [8, 1, 9, 2];
+        let selection = select(&bank, 2).unwrap();
+        assert_eq!(
+            highlight(&bank, &selection),
+            "81\x1b[1;32m9\x1b[0m\x1b[1;32m2\x1b[0m"
+        );
+        let none = select(&bank, 0).unwrap();
+        assert_eq!(highlight(&bank, &none), "8192");
+    }
+}
@@ -1,5 +1,114 @@
 use rayon::prelude::*;
 
+/// A grid of rolls packed one bit per cell, row by row. Column `c` lives at
+/// bit `c + 1` of its row, so bit 0 is an always-empty left border, and each
+/// row has as many words as its width needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Packs every `@` of `input`. The width is that of the longest line.
+    pub fn parse(input: &str) -> Self {
+        let lines: Vec<&[u8]> = input.lines().map(|l| l.as_bytes()).collect();
+        let rows = lines.len();
+        let cols = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let words_per_row = (cols + 1).div_ceil(64);
+        let mut bits = vec![0u64; rows * words_per_row];
+        for (r, line) in lines.iter().enumerate() {
+            for (c, &ch) in line.iter().enumerate() {
+                if ch == b'@' {
+                    let tc = c + 1;
+                    bits[r * words_per_row + (tc / 64)] |= 1 << (tc % 64);
+                }
+            }
+        }
+        BitGrid {
+            rows,
+            cols,
+            words_per_row,
+            bits,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn words_per_row(&self) -> usize {
+        self.words_per_row
+    }
+
+    /// Word `w` of row `r`, or 0 outside the grid.
+    pub fn word(&self, r: usize, w: usize) -> u64 {
+        if r < self.rows && w < self.words_per_row {
+            self.bits[r * self.words_per_row + w]
+        } else {
+            0
+        }
+    }
+
+    pub fn word_mut(&mut self, r: usize, w: usize) -> &mut u64 {
+        &mut self.bits[r * self.words_per_row + w]
+    }
+
+    /// Whether there is a roll at row `r`, column `c`.
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        let tc = c + 1;
+        (self.word(r, tc / 64) >> (tc % 64)) & 1 == 1
+    }
+
+    /// Number of rolls in the grid.
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The eight neighbour masks of word `w` in row `r`: bit `i` of each is
+    /// the neighbour of the cell at bit `i` in one direction, rows above
+    /// first.
+    pub fn neighbour_words(&self, r: usize, w: usize) -> [u64; 8] {
+        let mut ns = [0u64; 8];
+        for dr in -1..=1 {
+            let nr = r as i32 + dr;
+            if nr < 0 || nr >= self.rows as i32 {
+                continue;
+            }
+            let nr = nr as usize;
+            let w0 = self.word(nr, w);
+            let w_prev = if w > 0 { self.word(nr, w - 1) } else { 0 };
+            let w_next = self.word(nr, w + 1);
+            let low = (w0 << 1) | (w_prev >> 63);
+            let high = (w0 >> 1) | (w_next << 63);
+            match dr {
+                -1 => {
+                    ns[0] = low;
+                    ns[1] = w0;
+                    ns[2] = high;
+                }
+                0 => {
+                    ns[3] = low;
+                    ns[4] = high;
+                }
+                1 => {
+                    ns[5] = low;
+                    ns[6] = w0;
+                    ns[7] = high;
+                }
+                _ => unreachable!(),
+            }
+        }
+        ns
+    }
+}
+
 /// The "Autovectorized" version (Scalar)
 pub fn solve_part1_autovectorized(input: &str) -> usize {
     let lines: Vec<&[u8]> = input.lines().map(|l| l.as_bytes()).collect();
@@ -42,44 +151,26 @@ pub fn solve_part1_autovectorized(input: &str) -> usize {
 
 /// The "Bitpacked" version (Scalar)
 pub fn solve_part1_bitpacked(input: &str) -> usize {
-    let lines: Vec<&str> = input.lines().collect();
-    if lines.is_empty() {
-        return 0;
-    }
-    let (rows, cols) = (lines.len(), lines[0].len());
-    let words_per_row = 3;
-    let mut bitset = vec![0u64; rows * words_per_row];
-    for (r, line) in lines.iter().enumerate() {
-        for (c, ch) in line.bytes().enumerate() {
-            if ch == b'@' {
-                let tc = c + 1;
-                bitset[r * words_per_row + (tc / 64)] |= 1 << (tc % 64);
-            }
-        }
-    }
+    let grid = BitGrid::parse(input);
     let mut count = 0;
-    for r in 0..rows {
-        for c in 0..cols {
-            let tc = c + 1;
-            if (bitset[r * words_per_row + (tc / 64)] >> (tc % 64)) & 1 == 0 {
+    for r in 0..grid.rows() {
+        for c in 0..grid.cols() {
+            if !grid.get(r, c) {
                 continue;
             }
+            let tc = c + 1;
             let mut neighbors = 0;
             for dr in -1..=1 {
                 let nr = r as i32 + dr;
-                if nr < 0 || nr >= rows as i32 {
+                if nr < 0 || nr >= grid.rows() as i32 {
                     continue;
                 }
                 let nr = nr as usize;
                 let sb = tc - 1;
                 let ci = sb / 64;
                 let bo = sb % 64;
-                let w0 = bitset[nr * words_per_row + ci];
-                let w1 = if ci + 1 < words_per_row {
-                    bitset[nr * words_per_row + ci + 1]
-                } else {
-                    0
-                };
+                let w0 = grid.word(nr, ci);
+                let w1 = grid.word(nr, ci + 1);
                 let combined = (w0 as u128) | ((w1 as u128) << 64);
                 neighbors += ((combined >> bo) as u64 & 0b111).count_ones();
             }
@@ -93,66 +184,17 @@ pub fn solve_part1_bitpacked(input: &str) -> usize {
 
 /// The "Bit-Parallel SWAR" version (Multi-threaded)
 pub fn solve_part1_parallel_swar(input: &str) -> usize {
-    let lines: Vec<&str> = input.lines().collect();
-    if lines.is_empty() {
-        return 0;
-    }
-    let (rows, _cols) = (lines.len(), lines[0].len());
-    let words_per_row = 3;
-    let mut bitset = vec![0u64; rows * words_per_row];
-    for (r, line) in lines.iter().enumerate() {
-        for (c, ch) in line.bytes().enumerate() {
-            if ch == b'@' {
-                let tc = c + 1;
-                bitset[r * words_per_row + (tc / 64)] |= 1 << (tc % 64);
-            }
-        }
-    }
-
-    (0..rows)
+    let grid = BitGrid::parse(input);
+    (0..grid.rows())
         .into_par_iter()
         .map(|r| {
             let mut row_count = 0;
-            for w in 0..words_per_row {
-                let b = bitset[r * words_per_row + w];
+            for w in 0..grid.words_per_row() {
+                let b = grid.word(r, w);
                 if b == 0 {
                     continue;
                 }
-
-                let mut ns = [0u64; 8];
-                for dr in -1..=1 {
-                    let nr = r as i32 + dr;
-                    if nr < 0 || nr >= rows as i32 {
-                        continue;
-                    }
-                    let base_idx = nr as usize * words_per_row;
-                    let w0 = bitset[base_idx + w];
-                    let w_prev = if w > 0 { bitset[base_idx + w - 1] } else { 0 };
-                    let w_next = if w + 1 < words_per_row {
-                        bitset[base_idx + w + 1]
-                    } else {
-                        0
-                    };
-                    let low = (w0 << 1) | (w_prev >> 63);
-                    let high = (w0 >> 1) | (w_next << 63);
-                    match dr {
-                        -1 => {
-                            ns[0] = low;
-                            ns[1] = w0;
-                            ns[2] = high;
-                        }
-                        0 => {
-                            ns[3] = low;
-                            ns[4] = high;
-                        }
-                        1 => {
-                            ns[5] = low;
-                            ns[6] = w0;
-                            ns[7] = high;
-                        }
-                        _ => unreachable!(),
-                    }
-                }
+                let ns = grid.neighbour_words(r, w);
 
                 macro_rules! csa {
                     ($a:expr, $b:expr, $c:expr) => {
@@ -178,70 +220,20 @@ pub fn solve_part1_parallel_swar(input: &str) -> usize {
 /// The "Bit-Parallel SWAR" version for Part 2 (Single-threaded)
 /// Iteratively removes accessible rolls until stabilization.
 pub fn solve_part2_swar(input: &str) -> usize {
-    let lines: Vec<&str> = input.lines().collect();
-    if lines.is_empty() {
-        return 0;
-    }
-    let rows = lines.len();
-    let words_per_row = 3;
-    let mut bitset = vec![0u64; rows * words_per_row];
-
-    let mut initial_count = 0;
-    for (r, line) in lines.iter().enumerate() {
-        for (c, ch) in line.bytes().enumerate() {
-            if ch == b'@' {
-                let tc = c + 1;
-                bitset[r * words_per_row + (tc / 64)] |= 1 << (tc % 64);
-                initial_count += 1;
-            }
-        }
-    }
+    let mut grid = BitGrid::parse(input);
+    let initial_count = grid.count();
 
     loop {
         let mut changed = false;
-        let mut new_bitset = bitset.clone();
+        let mut next = grid.clone();
 
-        for r in 0..rows {
-            for w in 0..words_per_row {
-                let b = bitset[r * words_per_row + w];
+        for r in 0..grid.rows() {
+            for w in 0..grid.words_per_row() {
+                let b = grid.word(r, w);
                 if b == 0 {
                     continue;
                 }
-
-                let mut ns = [0u64; 8];
-                for dr in -1..=1 {
-                    let nr = r as i32 + dr;
-                    if nr < 0 || nr >= rows as i32 {
-                        continue;
-                    }
-                    let b_idx = nr as usize * words_per_row;
-                    let w0 = bitset[b_idx + w];
-                    let wp = if w > 0 { bitset[b_idx + w - 1] } else { 0 };
-                    let wn = if w + 1 < words_per_row {
-                        bitset[b_idx + w + 1]
-                    } else {
-                        0
-                    };
-                    let low = (w0 << 1) | (wp >> 63);
-                    let high = (w0 >> 1) | (wn << 63);
-                    match dr {
-                        -1 => {
-                            ns[0] = low;
-                            ns[1] = w0;
-                            ns[2] = high;
-                        }
-                        0 => {
-                            ns[3] = low;
-                            ns[4] = high;
-                        }
-                        1 => {
-                            ns[5] = low;
-                            ns[6] = w0;
-                            ns[7] = high;
-                        }
-                        _ => unreachable!(),
-                    }
-                }
+                let ns = grid.neighbour_words(r, w);
 
                 macro_rules! fa {
                     ($a:expr, $b:expr, $c:expr) => {
@@ -258,7 +250,7 @@ pub fn solve_part2_swar(input: &str) -> usize {
 
                 let accessible_mask = b & !(s2_f | s3_f);
                 if accessible_mask != 0 {
-                    new_bitset[r * words_per_row + w] &= !accessible_mask;
+                    *next.word_mut(r, w) &= !accessible_mask;
                     changed = true;
                 }
             }
@@ -266,75 +258,155 @@ pub fn solve_part2_swar(input: &str) -> usize {
         if !changed {
             break;
         }
-        bitset = new_bitset;
+        grid = next;
     }
 
-    let final_count: usize = bitset.iter().map(|w| w.count_ones() as usize).sum();
-    initial_count - final_count
+    initial_count - grid.count()
 }
 
-/// The "Bit-Parallel SWAR" version for Part 1 (Single-threaded)
-/// Processes 64 cells at a time using bitwise logic gates.
-pub fn solve_part1_swar_scalar(input: &str) -> usize {
-    let lines: Vec<&str> = input.lines().collect();
-    if lines.is_empty() {
-        return 0;
+/// What happens to one cell of the grid when rolls are removed round by
+/// round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    /// No roll here.
+    Empty,
+    /// The roll becomes accessible and is removed in this round, from 1.
+    Removed(usize),
+    /// The roll is never accessible.
+    Kept,
+}
+
+/// The "onion layers" of Part 2: the round in which every roll is removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layers {
+    /// One row per input line.
+    pub cells: Vec<Vec<Cell>>,
+    /// `per_round[i]`: rolls removed in round `i + 1`.
+    pub per_round: Vec<usize>,
+}
+
+impl Layers {
+    /// Rolls removed over all rounds.
+    pub fn total(&self) -> usize {
+        self.per_round.iter().sum()
+    }
+
+    /// The grid with each removed roll shown as its round: `1`-`9`, then
+    /// `a`-`z` for rounds 10 to 35 and `+` after that. Kept rolls stay `@`.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for row in &self.cells {
+            for cell in row {
+                out.push(match *cell {
+                    Cell::Empty => '.',
+                    Cell::Kept => '@',
+                    Cell::Removed(round) => char::from_digit(round as u32, 36)
+                        .filter(|_| round < 36)
+                        .unwrap_or('+'),
+                });
+            }
+            out.push('\n');
+        }
+        out
     }
-    let (rows, _cols) = (lines.len(), lines[0].len());
-    let words_per_row = 3;
-    let mut bitset = vec![0u64; rows * words_per_row];
+}
 
+/// Worklist peeling for Part 2, calling `on_removed(row, col, round)` for each
+/// roll as it goes and returning how many rolls each round removed.
+/// Keeps a neighbour count per roll and, like k-core peeling, only rechecks
+/// the rolls next to ones just removed. Each round's frontier is exactly the
+/// rolls that become accessible once the previous round is gone, so the work
+/// is proportional to the rolls and their neighbours, not rounds x grid.
+fn peel(input: &str, mut on_removed: impl FnMut(usize, usize, usize)) -> Vec<usize> {
+    let lines: Vec<&[u8]> = input.lines().map(|l| l.as_bytes()).collect();
+    let rows = lines.len();
+    let cols = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let stride = cols + 2;
+    let mut present = vec![false; (rows + 2) * stride];
     for (r, line) in lines.iter().enumerate() {
-        for (c, ch) in line.bytes().enumerate() {
-            if ch == b'@' {
-                let tc = c + 1;
-                bitset[r * words_per_row + (tc / 64)] |= 1 << (tc % 64);
+        for (c, &ch) in line.iter().enumerate() {
+            present[(r + 1) * stride + c + 1] = ch == b'@';
+        }
+    }
+    let offsets = [
+        -(stride as isize) - 1,
+        -(stride as isize),
+        -(stride as isize) + 1,
+        -1,
+        1,
+        stride as isize - 1,
+        stride as isize,
+        stride as isize + 1,
+    ];
+    let neighbours = |i: usize| offsets.map(|o| (i as isize + o) as usize);
+
+    let mut counts = vec![0u8; present.len()];
+    let mut frontier = Vec::new();
+    for i in (0..present.len()).filter(|&i| present[i]) {
+        counts[i] = neighbours(i).iter().filter(|&&n| present[n]).count() as u8;
+        if counts[i] < 4 {
+            frontier.push(i);
+        }
+    }
+
+    let mut per_round = Vec::new();
+    while !frontier.is_empty() {
+        per_round.push(frontier.len());
+        // Remove the whole round before counting, so a roll only joins the
+        // next frontier through rolls removed before it.
+        for &i in &frontier {
+            present[i] = false;
+            on_removed(i / stride - 1, i % stride - 1, per_round.len());
+        }
+        let mut next = Vec::new();
+        for &i in &frontier {
+            for n in neighbours(i) {
+                if present[n] {
+                    counts[n] -= 1;
+                    if counts[n] == 3 {
+                        next.push(n);
+                    }
+                }
             }
         }
+        frontier = next;
     }
+    per_round
+}
+
+/// The "Worklist Peeling" version of Part 2, returning the round each roll
+/// is removed in.
+pub fn peel_layers(input: &str) -> Layers {
+    let mut cells: Vec<Vec<Cell>> = input
+        .lines()
+        .map(|line| {
+            line.bytes()
+                .map(|ch| if ch == b'@' { Cell::Kept } else { Cell::Empty })
+                .collect()
+        })
+        .collect();
+    let per_round = peel(input, |r, c, round| cells[r][c] = Cell::Removed(round));
+    Layers { cells, per_round }
+}
 
+/// The "Worklist Peeling" version for Part 2 (Single-threaded). Counts the
+/// removals without building the per-cell map.
+pub fn solve_part2_peeling(input: &str) -> usize {
+    peel(input, |_, _, _| {}).iter().sum()
+}
+
+/// The "Bit-Parallel SWAR" version for Part 1 (Single-threaded)
+/// Processes 64 cells at a time using bitwise logic gates.
+pub fn solve_part1_swar_scalar(input: &str) -> usize {
+    let grid = BitGrid::parse(input);
     let mut count = 0;
-    for r in 0..rows {
-        for w in 0..words_per_row {
-            let b = bitset[r * words_per_row + w];
+    for r in 0..grid.rows() {
+        for w in 0..grid.words_per_row() {
+            let b = grid.word(r, w);
             if b == 0 {
                 continue;
             }
-
-            let mut ns = [0u64; 8];
-            for dr in -1..=1 {
-                let nr = r as i32 + dr;
-                if nr < 0 || nr >= rows as i32 {
-                    continue;
-                }
-                let base_idx = nr as usize * words_per_row;
-                let w0 = bitset[base_idx + w];
-                let w1 = if w > 0 { bitset[base_idx + w - 1] } else { 0 };
-                let w2 = if w + 1 < words_per_row {
-                    bitset[base_idx + w + 1]
-                } else {
-                    0
-                };
-                let low = (w0 << 1) | (w1 >> 63);
-                let high = (w0 >> 1) | (w2 << 63);
-                match dr {
-                    -1 => {
-                        ns[0] = low;
-                        ns[1] = w0;
-                        ns[2] = high;
-                    }
-                    0 => {
-                        ns[3] = low;
-                        ns[4] = high;
-                    }
-                    1 => {
-                        ns[5] = low;
-                        ns[6] = w0;
-                        ns[7] = high;
-                    }
-                    _ => unreachable!(),
-                }
-            }
+            let ns = grid.neighbour_words(r, w);
 
             macro_rules! csa {
                 ($a:expr, $b:expr, $c:expr) => {
@@ -358,6 +430,7 @@ pub fn solve_part1_swar_scalar(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::grid;
     const EX: &str = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.\n@@.@@@@.@@\n.@@@@@@@.@\n.@.@.@.@@@\n@.@@@.@@@@\n.@@@@@@@@.\n@.@.@@@.@.";
 
     #[test]
@@ -375,5 +448,175 @@ mod tests {
     #[test]
     fn test_p2() {
         assert_eq!(solve_part2_swar(EX), 43);
+        assert_eq!(solve_part2_peeling(EX), 43);
+    }
+
+    /// Part 2 by removing accessible rolls from a plain grid, round by round.
+    fn removed_naive(input: &str) -> usize {
+        let mut cells: Vec<Vec<bool>> = input
+            .lines()
+            .map(|l| l.bytes().map(|b| b == b'@').collect())
+            .collect();
+        let mut removed = 0;
+        loop {
+            let mut accessible = Vec::new();
+            for r in 0..cells.len() {
+                for c in 0..cells[r].len() {
+                    if !cells[r][c] {
+                        continue;
+                    }
+                    let n = (-1..=1_isize)
+                        .flat_map(|dr| (-1..=1_isize).map(move |dc| (dr, dc)))
+                        .filter(|&d| d != (0, 0))
+                        .filter(|&(dr, dc)| {
+                            let (nr, nc) = (r as isize + dr, c as isize + dc);
+                            nr >= 0
+                                && nc >= 0
+                                && cells
+                                    .get(nr as usize)
+                                    .and_then(|row| row.get(nc as usize))
+                                    .copied()
+                                    .unwrap_or(false)
+                        })
+                        .count();
+                    if n < 4 {
+                        accessible.push((r, c));
+                    }
+                }
+            }
+            if accessible.is_empty() {
+                return removed;
+            }
+            removed += accessible.len();
+            for (r, c) in accessible {
+                cells[r][c] = false;
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitgrid_layout() {
+        for (cols, words) in [
+            (1, 1),
+            (63, 1),
+            (64, 2),
+            (65, 2),
+            (127, 2),
+            (128, 3),
+            (10_000, 157),
+        ] {
+            let input = grid(3, cols, cols as u64 + 1);
+            let bits = BitGrid::parse(&input);
+            assert_eq!((bits.rows(), bits.cols()), (3, cols));
+            assert_eq!(bits.words_per_row(), words);
+            assert_eq!(bits.count(), input.matches('@').count());
+            let rows: Vec<&[u8]> = input.lines().map(|l| l.as_bytes()).collect();
+            for (r, row) in rows.iter().enumerate() {
+                for (c, &ch) in row.iter().enumerate() {
+                    assert_eq!(bits.get(r, c), ch == b'@');
+                }
+            }
+        }
+        assert_eq!(BitGrid::parse("").words_per_row(), 1);
+    }
+
+    #[test]
+    fn test_any_width() {
+        for cols in [1, 2, 63, 64, 65, 191, 192, 10_000] {
+            for rows in [1, 2, 7] {
+                let input = grid(rows, cols, (rows * 10_007 + cols) as u64);
+                let expected = solve_part1_autovectorized(&input);
+                assert_eq!(solve_part1_bitpacked(&input), expected, "{}x{}", rows, cols);
+                assert_eq!(
+                    solve_part1_parallel_swar(&input),
+                    expected,
+                    "{}x{}",
+                    rows,
+                    cols
+                );
+                assert_eq!(
+                    solve_part1_swar_scalar(&input),
+                    expected,
+                    "{}x{}",
+                    rows,
+                    cols
+                );
+                assert_eq!(
+                    solve_part2_swar(&input),
+                    removed_naive(&input),
+                    "{}x{}",
+                    rows,
+                    cols
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_peeling_deep_grid() {
+        // A two-row strip loses one column from each end per round.
+        let strip = format!("{}\n", "@".repeat(300)).repeat(2);
+        assert_eq!(solve_part2_swar(&strip), 600);
+        assert_eq!(solve_part2_peeling(&strip), 600);
+        // A solid block only loses its corners.
+        let block = format!("{}\n", "@".repeat(150)).repeat(150);
+        assert_eq!(solve_part2_swar(&block), 4);
+        assert_eq!(solve_part2_peeling(&block), 4);
+        assert_eq!(solve_part2_peeling(""), 0);
+        assert_eq!(solve_part2_peeling("...\n.@.\n"), 1);
+    }
+
+    #[test]
+    fn test_layers() {
+        let layers = peel_layers(EX);
+        assert_eq!(layers.per_round, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(layers.total(), 43);
+        assert_eq!(layers.cells[0][2], Cell::Removed(1));
+        assert_eq!(layers.cells[0][0], Cell::Empty);
+        let rendered = layers.render();
+        assert_eq!(rendered.lines().count(), 10);
+        assert_eq!(rendered.matches('@').count(), EX.matches('@').count() - 43);
+        assert_eq!(rendered.matches('1').count(), 13);
+        assert_eq!(rendered.lines().next(), Some("..11.1121."));
+
+        // The round of every roll agrees with removing rolls round by round.
+        let input = grid(20, 70, 0x1a7e);
+        let layers = peel_layers(&input);
+        let mut left = input.clone();
+        for (i, &removed) in layers.per_round.iter().enumerate() {
+            let accessible: Vec<(usize, usize)> = layers
+                .cells
+                .iter()
+                .enumerate()
+                .flat_map(|(r, row)| {
+                    row.iter()
+                        .enumerate()
+                        .filter(move |&(_, &cell)| cell == Cell::Removed(i + 1))
+                        .map(move |(c, _)| (r, c))
+                })
+                .collect();
+            assert_eq!(accessible.len(), removed);
+            assert_eq!(solve_part1_autovectorized(&left), removed);
+            let mut rows: Vec<Vec<u8>> = left.lines().map(|l| l.as_bytes().to_vec()).collect();
+            for (r, c) in accessible {
+                rows[r][c] = b'.';
+            }
+            left = rows
+                .into_iter()
+                .map(|row| String::from_utf8(row).unwrap() + "\n")
+                .collect();
+        }
+        assert_eq!(solve_part1_autovectorized(&left), 0);
+        assert_eq!(removed_naive(&input), layers.total());
+    }
+
+    #[test]
+    fn test_render_many_rounds() {
+        let strip = format!("{}\n", "@".repeat(80)).repeat(2);
+        let layers = peel_layers(&strip);
+        assert_eq!(layers.per_round.len(), 40);
+        let first = layers.render().lines().next().unwrap().to_string();
+        assert!(first.starts_with("123456789abc"));
+        assert_eq!(&first[34..36], "z+");
     }
 }
@@ -1,9 +1,46 @@
 mod experimental;
+#[cfg(test)]
+mod testing;
 use std::time::Instant;
 
 fn main() {
     let input = include_str!("../input.txt");
 
+    // day-04 --layers: the round each roll is removed in, for forklift waves.
+    if std::env::args().nth(1).as_deref() == Some("--layers") {
+        let layers = experimental::peel_layers(input);
+        print!("{}", layers.render());
+        for (i, removed) in layers.per_round.iter().enumerate() {
+            println!("Round {}: {} rolls", i + 1, removed);
+        }
+        println!("Total removed rolls: {}", layers.total());
+        return;
+    }
+
+    // day-04 --deep: Part 2 SWAR against peeling on a grid that peels slowly.
+    // The puzzle grid peels in a few dozen rounds. Two-row strips of rolls
+    // peel one column from each end per round, so a 2000-wide grid of them
+    // takes 1000 rounds, each of which the SWAR version rescans in full.
+    if std::env::args().nth(1).as_deref() == Some("--deep") {
+        let strip = format!("{}\n", "@".repeat(2000)).repeat(2);
+        let deep = format!("{}{}\n", strip, ".".repeat(2000)).repeat(100);
+        let start = Instant::now();
+        let deep_swar = experimental::solve_part2_swar(&deep);
+        let duration = start.elapsed();
+        println!(
+            "Part 2 - Deep strips (SWAR):            {} ({:?})",
+            deep_swar, duration
+        );
+        let start = Instant::now();
+        let deep_peeling = experimental::solve_part2_peeling(&deep);
+        let duration = start.elapsed();
+        println!(
+            "Part 2 - Deep strips (Peeling):         {} ({:?})",
+            deep_peeling, duration
+        );
+        return;
+    }
+
     let start = Instant::now();
     let part1 = solve_part1(input);
     let duration = start.elapsed();
@@ -51,6 +88,14 @@ fn main() {
         "Part 2 - Total removed rolls:           {} ({:?})",
         part2, duration
     );
+
+    let start = Instant::now();
+    let part2_peeling = experimental::solve_part2_peeling(input);
+    let duration = start.elapsed();
+    println!(
+        "Part 2 - Total removed rolls (Peeling): {} ({:?})",
+        part2_peeling, duration
+    );
 }
 
 fn solve_part1(input: &str) -> usize {
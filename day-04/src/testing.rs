@@ -0,0 +1,27 @@
+//! Fixtures shared by the unit tests.
+
+/// Pseudo-random states from a xorshift generator, the same on every run so
+/// a failing case can be reproduced.
+pub fn xorshift(seed: u64) -> impl Iterator<Item = u64> {
+    std::iter::successors(Some(seed), |&state| {
+        let mut state = state;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        Some(state)
+    })
+    .skip(1)
+}
+
+/// A pseudo-random grid, about half rolls.
+pub fn grid(rows: usize, cols: usize, seed: u64) -> String {
+    let mut states = xorshift(seed);
+    let mut out = String::new();
+    for _ in 0..rows {
+        for state in states.by_ref().take(cols) {
+            out.push(if state % 5 < 3 { '@' } else { '.' });
+        }
+        out.push('\n');
+    }
+    out
+}